@@ -1,17 +1,16 @@
-use std::{ffi::c_int, io::SeekFrom};
+use std::io::SeekFrom;
 
 use anyhow::{ensure, Context};
 use cdparanoia::{CdromDrive, CdromParanoia, ParanoiaMode, Verbosity};
 use hound::{SampleFormat, WavSpec, WavWriter};
-use libc::c_long;
 
 fn main() -> anyhow::Result<()> {
     let drive =
         CdromDrive::find_a_cdrom(Verbosity::PrintIt).context("failed to find a CD drive.")?;
     drive.open().context("failed to open drive")?;
-    let mut paranoia = CdromParanoia::init(drive);
+    let mut paranoia = CdromParanoia::init(drive).context("failed to initialize paranoia")?;
 
-    paranoia.set_mode(ParanoiaMode::FULL);
+    paranoia.set_mode(ParanoiaMode::FULL)?;
     paranoia
         .drive()
         .set_verbosity(Verbosity::PrintIt, Verbosity::PrintIt);
@@ -42,7 +41,9 @@ fn main() -> anyhow::Result<()> {
     );
 
     for _ in first_sector..=last_sector {
-        let data = paranoia.read(event_callback);
+        let data = paranoia.read_with(|position, event| {
+            eprintln!("EV: position {}: {} ({})", position, event, event.to_raw());
+        });
 
         for &sample in data {
             output.write_sample(sample)?;
@@ -64,25 +65,3 @@ fn main() -> anyhow::Result<()> {
 
     Ok(())
 }
-
-extern "C" fn event_callback(position: c_long, event: c_int) {
-    use cdparanoia_sys::*;
-    let description = match event as u32 {
-        PARANOIA_CB_READ => "read",
-        PARANOIA_CB_VERIFY => "verifying jitter",
-        PARANOIA_CB_FIXUP_EDGE => "fixed edge jitter",
-        PARANOIA_CB_FIXUP_ATOM => "fixed atom jitter",
-        PARANOIA_CB_SCRATCH => "scratch",
-        PARANOIA_CB_REPAIR => "repair",
-        PARANOIA_CB_SKIP => "skip exhausted retry",
-        PARANOIA_CB_DRIFT => "drift exhausted retry",
-        PARANOIA_CB_BACKOFF => "backoff",
-        PARANOIA_CB_OVERLAP => "dynamic overlap adjust",
-        PARANOIA_CB_FIXUP_DROPPED => "fixed dropped bytes",
-        PARANOIA_CB_FIXUP_DUPED => "fixed duplicated bytes",
-        PARANOIA_CB_READERR => "read error",
-        PARANOIA_CB_CACHEERR => "cache error",
-        _ => "unknown",
-    };
-    eprintln!("EV: position {}: {} ({})", position, description, event);
-}
@@ -0,0 +1,285 @@
+//! AccurateRip checksums and track verification.
+
+use std::ops::RangeInclusive;
+
+use crate::{CdromParanoia, DiscLayout, Error, ErrorCode, ImageDrive, CD_FRAMESAMPLES};
+
+/// Frames excluded at the start of the first track and the end of the last
+/// track, where drives with different read offsets disagree.
+const EDGE_FRAMES: u64 = 5 * CD_FRAMESAMPLES as u64;
+
+/// Which AccurateRip checksum matched.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AccurateRipVersion {
+    V1,
+    V2,
+}
+
+/// Outcome of [`CdromParanoia::verify_track`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum VerifyResult {
+    Matched(AccurateRipVersion),
+    NoMatch,
+}
+
+/// Computes the AccurateRip v1 and v2 checksums of one track's interleaved
+/// stereo samples.
+///
+/// `first_track` and `last_track` say whether this is the first or last
+/// audio track of the disc, whose outer five sectors are not checksummed.
+pub fn accuraterip_checksums(samples: &[i16], first_track: bool, last_track: bool) -> (u32, u32) {
+    let frames = (samples.len() / 2) as u64;
+    let check_from = if first_track { EDGE_FRAMES } else { 1 };
+    let check_to = if last_track {
+        frames.saturating_sub(EDGE_FRAMES)
+    } else {
+        frames
+    };
+
+    let mut v1: u32 = 0;
+    let mut v2: u32 = 0;
+    for (frame, multiplier) in samples.chunks_exact(2).zip(1u64..) {
+        if multiplier < check_from || multiplier > check_to {
+            continue;
+        }
+        let value = (frame[0] as u16 as u32 | (frame[1] as u16 as u32) << 16) as u64;
+        let product = value * multiplier;
+        v1 = v1.wrapping_add(product as u32);
+        v2 = v2
+            .wrapping_add(product as u32)
+            .wrapping_add((product >> 32) as u32);
+    }
+    (v1, v2)
+}
+
+impl VerifyResult {
+    fn from_checksums(actual: (u32, u32), expected_v1: u32, expected_v2: u32) -> Self {
+        if actual.1 == expected_v2 {
+            Self::Matched(AccurateRipVersion::V2)
+        } else if actual.0 == expected_v1 {
+            Self::Matched(AccurateRipVersion::V1)
+        } else {
+            Self::NoMatch
+        }
+    }
+}
+
+/// Reads `track`'s samples shifted by `offset` samples (frames), as a drive
+/// with that read offset correction would return them. Samples outside
+/// `audio` (absolute sectors) cannot be read and are zero.
+fn read_with_offset(
+    track: RangeInclusive<u64>,
+    offset: i32,
+    audio: RangeInclusive<u64>,
+    mut read_range: impl FnMut(RangeInclusive<u64>) -> Result<Vec<i16>, Error>,
+) -> Result<Vec<i16>, Error> {
+    let frames_per_sector = CD_FRAMESAMPLES as i64;
+    let start = *track.start() as i64 * frames_per_sector + offset as i64;
+    let end = (*track.end() as i64 + 1) * frames_per_sector + offset as i64;
+
+    let first_sector = start
+        .div_euclid(frames_per_sector)
+        .max(*audio.start() as i64);
+    let last_sector = (end - 1)
+        .div_euclid(frames_per_sector)
+        .min(*audio.end() as i64);
+
+    let mut samples = vec![0; (end - start) as usize * 2];
+    if first_sector <= last_sector {
+        let data = read_range(first_sector as u64..=last_sector as u64)?;
+        let data_start = first_sector * frames_per_sector;
+        let copy_from = start.max(data_start);
+        let copy_to = end.min(data_start + (data.len() / 2) as i64);
+        if copy_from < copy_to {
+            let src = (copy_from - data_start) as usize * 2..(copy_to - data_start) as usize * 2;
+            let dst = (copy_from - start) as usize * 2;
+            samples[dst..dst + src.len()].copy_from_slice(&data[src]);
+        }
+    }
+    Ok(samples)
+}
+
+impl CdromParanoia {
+    /// Rips `track` with the drive's read `offset` (in samples) applied and
+    /// checks it against the AccurateRip checksums from the database.
+    pub fn verify_track(
+        &mut self,
+        track: u32,
+        offset: i32,
+        expected_v1: u32,
+        expected_v2: u32,
+    ) -> Result<VerifyResult, Error> {
+        let layout = self.drive().read_layout()?;
+        let (first_track, last_track) = audio_position(&layout, track)?;
+        let track = *layout.track(track).unwrap();
+        let audio = self.drive().disc_first_sector()?..=self.drive().disc_last_sector()?;
+
+        let samples = read_with_offset(
+            track.first_sector..=track.last_sector,
+            offset,
+            audio,
+            |range| self.read_range_with(range, None),
+        )?;
+        Ok(VerifyResult::from_checksums(
+            accuraterip_checksums(&samples, first_track, last_track),
+            expected_v1,
+            expected_v2,
+        ))
+    }
+}
+
+impl ImageDrive {
+    /// See [`CdromParanoia::verify_track`].
+    pub fn verify_track(
+        &mut self,
+        track: u32,
+        offset: i32,
+        expected_v1: u32,
+        expected_v2: u32,
+    ) -> Result<VerifyResult, Error> {
+        let layout = self.layout().clone();
+        let (first_track, last_track) = audio_position(&layout, track)?;
+        let track = *layout.track(track).unwrap();
+        let audio_tracks = || layout.tracks.iter().filter(|track| track.audio);
+        let audio = audio_tracks().next().unwrap().first_sector
+            ..=audio_tracks().next_back().unwrap().last_sector;
+
+        let samples = read_with_offset(
+            track.first_sector..=track.last_sector,
+            offset,
+            audio,
+            |range| self.read_range(range),
+        )?;
+        Ok(VerifyResult::from_checksums(
+            accuraterip_checksums(&samples, first_track, last_track),
+            expected_v1,
+            expected_v2,
+        ))
+    }
+}
+
+/// Returns whether `track` is the first and/or last audio track.
+fn audio_position(layout: &DiscLayout, track: u32) -> Result<(bool, bool), Error> {
+    let info = layout
+        .track(track)
+        .ok_or(Error::from_code(ErrorCode::InvalidTrackNumber))?;
+    if !info.audio {
+        return Err(Error::from_code(ErrorCode::TrackNotAudio));
+    }
+    let mut audio = layout.tracks.iter().filter(|t| t.audio).map(|t| t.number);
+    let first = audio.next() == Some(track);
+    let last = layout
+        .tracks
+        .iter()
+        .rev()
+        .find(|t| t.audio)
+        .map(|t| t.number)
+        == Some(track);
+    Ok((first, last))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn weights_frames_by_position() {
+        // Frames with value 1 (left = 1, right = 0) at positions 1..=3.
+        assert_eq!(
+            accuraterip_checksums(&[1, 0, 1, 0, 1, 0], false, false),
+            (6, 6)
+        );
+        // 0xffffffff * 2 overflows: v1 keeps the low word, v2 adds the
+        // high word back in.
+        assert_eq!(
+            accuraterip_checksums(&[0, 0, -1, -1], false, false),
+            (0xffff_fffe, 0xffff_ffff)
+        );
+    }
+
+    #[test]
+    fn skips_disc_edges() {
+        let frames = 3 * EDGE_FRAMES as usize;
+        let base = vec![1; frames * 2];
+        let checksum = |samples: &[i16]| accuraterip_checksums(samples, true, true);
+
+        let mut start = base.clone();
+        start[2 * (EDGE_FRAMES as usize - 2)] = 7;
+        assert_eq!(checksum(&start), checksum(&base));
+        start[2 * (EDGE_FRAMES as usize - 1)] = 7;
+        assert_ne!(checksum(&start), checksum(&base));
+
+        let mut end = base.clone();
+        end[2 * (frames - EDGE_FRAMES as usize)] = 7;
+        assert_eq!(checksum(&end), checksum(&base));
+        end[2 * (frames - EDGE_FRAMES as usize - 1)] = 7;
+        assert_ne!(checksum(&end), checksum(&base));
+    }
+
+    #[test]
+    fn verifies_image_track() {
+        let path =
+            std::env::temp_dir().join(format!("cdparanoia-verify-{}.bin", std::process::id()));
+        let bytes: Vec<u8> = (0..24 * CD_FRAMESAMPLES * 2)
+            .flat_map(|i| ((i * 7919 % 65521) as i16).to_le_bytes())
+            .collect();
+        std::fs::write(&path, bytes).unwrap();
+
+        let track = |number, first_sector, last_sector| crate::Track {
+            number,
+            first_sector,
+            last_sector,
+            channels: 2,
+            audio: true,
+            copy_permitted: false,
+            preemphasis: false,
+        };
+        let layout = DiscLayout {
+            tracks: vec![track(1, 0, 11), track(2, 12, 23)],
+            lead_out: 24,
+        };
+        let mut image = ImageDrive::open(&path, &layout).unwrap();
+        let (v1, v2) = accuraterip_checksums(&image.read_track(2).unwrap(), false, true);
+
+        assert_eq!(
+            image.verify_track(2, 0, !v1, v2).unwrap(),
+            VerifyResult::Matched(AccurateRipVersion::V2)
+        );
+        assert_eq!(
+            image.verify_track(2, 0, v1, !v2).unwrap(),
+            VerifyResult::Matched(AccurateRipVersion::V1)
+        );
+        assert_eq!(
+            image.verify_track(2, 0, !v1, !v2).unwrap(),
+            VerifyResult::NoMatch
+        );
+        assert_eq!(
+            image.verify_track(2, 30, v1, v2).unwrap(),
+            VerifyResult::NoMatch
+        );
+        std::fs::remove_file(path).unwrap();
+    }
+
+    #[test]
+    fn offset_shifts_and_zero_fills() {
+        // Every sample holds its absolute frame index (mod 2^15).
+        let read = |range: RangeInclusive<u64>| -> Result<Vec<i16>, Error> {
+            let frames = *range.start() * 588..(*range.end() + 1) * 588;
+            Ok(frames.flat_map(|f| [f as i16, f as i16]).collect())
+        };
+
+        let shifted = read_with_offset(2..=3, 667, 0..=9, read).unwrap();
+        assert_eq!(shifted.len(), 2 * 588 * 2);
+        assert_eq!(shifted[0], (2 * 588 + 667) as i16);
+        assert_eq!(shifted[shifted.len() - 1], (4 * 588 + 667 - 1) as i16);
+
+        let early = read_with_offset(0..=0, -10, 0..=9, read).unwrap();
+        assert_eq!(&early[..20], &[0; 20]);
+        assert_eq!(early[20], 0);
+        assert_eq!(early[22], 1);
+
+        let late = read_with_offset(9..=9, 10, 0..=9, read).unwrap();
+        assert_eq!(late[2 * 577], (9 * 588 + 587) as i16);
+        assert_eq!(&late[2 * 578..], &[0; 20]);
+    }
+}
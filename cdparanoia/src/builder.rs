@@ -0,0 +1,198 @@
+//! One-shot setup of a paranoia session.
+
+use std::{
+    ffi::c_int,
+    path::{Path, PathBuf},
+};
+
+use crate::{
+    canonical_device, CdromDrive, CdromParanoia, Error, ErrorCode, FindError, Interface,
+    ParanoiaMode, Verbosity,
+};
+
+/// Finds or identifies a drive, opens it and starts a configured paranoia
+/// session, replacing the usual sequence of calls:
+///
+/// ```no_run
+/// # use cdparanoia::{ParanoiaBuilder, ParanoiaMode};
+/// let paranoia = ParanoiaBuilder::new()
+///     .device("/dev/sr0")
+///     .mode(ParanoiaMode::FULL)
+///     .speed(8)
+///     .open()?;
+/// # Ok::<(), cdparanoia::FindError>(())
+/// ```
+///
+/// Settings left out keep the library's defaults.
+#[derive(Debug, Clone)]
+pub struct ParanoiaBuilder {
+    device: Option<PathBuf>,
+    interface: Option<Interface>,
+    verbosity: Verbosity,
+    mode: Option<ParanoiaMode>,
+    speed: Option<i32>,
+    overlap: Option<i64>,
+}
+
+impl Default for ParanoiaBuilder {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl ParanoiaBuilder {
+    pub fn new() -> Self {
+        Self {
+            device: None,
+            interface: None,
+            verbosity: Verbosity::ForgetIt,
+            mode: None,
+            speed: None,
+            overlap: None,
+        }
+    }
+
+    /// Uses the drive at `path`, canonicalized as in
+    /// [`CdromDrive::identify_path`]. Without a device, the usual nodes are
+    /// probed as in [`CdromDrive::find_a_cdrom`].
+    pub fn device(mut self, path: impl AsRef<Path>) -> Self {
+        self.device = Some(path.as_ref().to_owned());
+        self
+    }
+
+    /// Accesses the drive through `interface` instead of letting the library
+    /// pick one, e.g. [`Interface::SgioScsi`] for a drive whose cooked
+    /// ioctls misbehave. Needs a [`device`](Self::device).
+    ///
+    /// [`Interface::CookedIoctl`] identifies the drive as
+    /// [`CdromDrive::identify_cooked`] does; the SCSI interfaces as
+    /// [`CdromDrive::identify_scsi`] does, with the device as both the
+    /// generic and the ioctl device, leaving the choice between them to
+    /// the library. If the drive cannot be identified that way,
+    /// [`open`](Self::open) fails rather than falling back. The simulated
+    /// [`Interface::Test`] drive cannot be forced and fails with
+    /// [`ErrorCode::InterfaceNotSupported`].
+    pub fn force_interface(mut self, interface: Interface) -> Self {
+        self.interface = Some(interface);
+        self
+    }
+
+    /// Where the library's messages and errors go, while identifying and
+    /// opening and afterwards. Defaults to [`Verbosity::ForgetIt`]; use
+    /// [`Verbosity::LogIt`] to have a failure carry the messages.
+    pub fn verbosity(mut self, verbosity: Verbosity) -> Self {
+        self.verbosity = verbosity;
+        self
+    }
+
+    /// See [`CdromParanoia::set_mode`].
+    pub fn mode(mut self, mode: ParanoiaMode) -> Self {
+        self.mode = Some(mode);
+        self
+    }
+
+    /// See [`CdromDrive::set_speed`].
+    pub fn speed(mut self, speed: i32) -> Self {
+        self.speed = Some(speed);
+        self
+    }
+
+    /// See [`CdromParanoia::set_overlap`].
+    pub fn overlap(mut self, overlap: i64) -> Self {
+        self.overlap = Some(overlap);
+        self
+    }
+
+    /// Identifies and opens the drive, then starts and configures the
+    /// session.
+    ///
+    /// A failed open carries the library's error code, e.g.
+    /// [`ErrorCode::NoMediumPresent`](crate::ErrorCode::NoMediumPresent),
+    /// as does a setting the drive rejects. Whatever was set up by then is
+    /// closed again before returning.
+    pub fn open(self) -> Result<CdromParanoia, FindError> {
+        let verbosity = self.verbosity as c_int;
+        let drive = match (&self.device, self.interface) {
+            (None, None) => CdromDrive::find_and_open(self.verbosity)?,
+            (None, Some(_)) => {
+                return Err(FindError {
+                    error: None,
+                    messages: "forcing an interface needs a device\n".to_owned(),
+                })
+            }
+            (Some(path), None) => {
+                let device = canonical_device(path)?;
+                CdromDrive::identify_and_open(|messages| unsafe {
+                    cdparanoia_sys::cdda_identify(device.as_ptr(), verbosity, messages)
+                })?
+            }
+            (Some(path), Some(Interface::CookedIoctl)) => {
+                CdromDrive::open_cooked(&canonical_device(path)?, self.verbosity)?
+            }
+            (Some(path), Some(interface)) if interface.is_scsi() => {
+                let device = canonical_device(path)?;
+                CdromDrive::open_scsi(&device, &device, self.verbosity)?
+            }
+            (Some(_), Some(_)) => {
+                return Err(FindError {
+                    error: Some(Error::from_code(ErrorCode::InterfaceNotSupported)),
+                    messages: String::new(),
+                })
+            }
+        };
+        drive.set_verbosity(self.verbosity, self.verbosity);
+
+        let failed = |error| FindError {
+            error: Some(error),
+            messages: String::new(),
+        };
+        if let Some(speed) = self.speed {
+            drive.set_speed(speed).map_err(failed)?;
+        }
+        let paranoia = CdromParanoia::init(drive).map_err(failed)?;
+        if let Some(mode) = self.mode {
+            paranoia.set_mode(mode).map_err(failed)?;
+        }
+        if let Some(overlap) = self.overlap {
+            paranoia.set_overlap(overlap).map_err(failed)?;
+        }
+        Ok(paranoia)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn missing_device_is_not_found() {
+        let error = ParanoiaBuilder::new()
+            .device("/nonexistent/sr0")
+            .open()
+            .err()
+            .unwrap();
+        assert_eq!(error.error(), None);
+        assert!(error.messages().starts_with("/nonexistent/sr0: "));
+    }
+
+    #[test]
+    fn forced_interface_needs_supported_device() {
+        let error = ParanoiaBuilder::new()
+            .force_interface(Interface::SgioScsi)
+            .open()
+            .err()
+            .unwrap();
+        assert_eq!(error.error(), None);
+
+        let error = ParanoiaBuilder::new()
+            .device("/")
+            .force_interface(Interface::Test)
+            .open()
+            .err()
+            .unwrap();
+        assert_eq!(
+            error.error(),
+            Some(&Error::from_code(ErrorCode::InterfaceNotSupported))
+        );
+    }
+}
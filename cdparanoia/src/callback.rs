@@ -0,0 +1,127 @@
+//! Routing of paranoia's C callback into Rust closures.
+//!
+//! `paranoia_read` takes a bare function pointer with no user-data slot, so
+//! the closure for the read in progress is stashed in a thread-local while
+//! the library runs, and a fixed trampoline forwards events to it.
+//!
+//! Unwinding out of the trampoline into C would be undefined behavior, so a
+//! panic in the closure is caught there. The trampoline then ignores the
+//! remaining events of that call, lets the library return normally, and
+//! the panic is resumed from `with_sink`.
+
+use std::{
+    any::Any,
+    cell::Cell,
+    ffi::{c_int, c_long},
+    panic::{self, AssertUnwindSafe},
+    ptr::null_mut,
+};
+
+use libc::c_void;
+
+pub(crate) type RawCallback = unsafe extern "C" fn(c_long, c_int);
+
+type Sink<'a> = &'a mut dyn FnMut(c_long, c_int);
+
+struct Installed<'a> {
+    sink: Sink<'a>,
+    /// Payload of a panic caught in `sink`.
+    panic: Option<Box<dyn Any + Send>>,
+}
+
+thread_local! {
+    /// Points to an `Installed` on the stack of `with_sink`, or null.
+    static SINK: Cell<*mut c_void> = const { Cell::new(null_mut()) };
+}
+
+/// Restores the previously installed sink, even when unwinding.
+struct Restore(*mut c_void);
+
+impl Drop for Restore {
+    fn drop(&mut self) {
+        SINK.with(|sink| sink.set(self.0));
+    }
+}
+
+/// Runs `f` with `sink` receiving every event passed to the callback that
+/// `f` is given, on this thread, until `f` returns.
+///
+/// If `sink` panics, it receives no further events and the panic is resumed
+/// after `f` returns.
+pub(crate) fn with_sink<R>(
+    sink: &mut dyn FnMut(c_long, c_int),
+    f: impl FnOnce(RawCallback) -> R,
+) -> R {
+    let mut installed = Installed { sink, panic: None };
+    let ptr = &mut installed as *mut Installed<'_> as *mut c_void;
+    let restore = Restore(SINK.with(|current| current.replace(ptr)));
+    let result = f(trampoline);
+    drop(restore);
+    if let Some(payload) = installed.panic {
+        panic::resume_unwind(payload);
+    }
+    result
+}
+
+unsafe extern "C" fn trampoline(position: c_long, event: c_int) {
+    let ptr = SINK.with(|sink| sink.get());
+    if !ptr.is_null() {
+        // Only set by `with_sink`, which outlives the library call.
+        let installed = unsafe { &mut *(ptr as *mut Installed<'_>) };
+        if installed.panic.is_none() {
+            let sink = &mut installed.sink;
+            if let Err(payload) = panic::catch_unwind(AssertUnwindSafe(|| sink(position, event))) {
+                installed.panic = Some(payload);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn forwards_to_innermost_sink_and_restores() {
+        let mut outer = Vec::new();
+        let mut inner = Vec::new();
+        let mut outer_sink = |position, event| outer.push((position, event));
+        with_sink(&mut outer_sink, |callback| unsafe {
+            callback(1, 10);
+            let mut inner_sink = |position, event| inner.push((position, event));
+            with_sink(&mut inner_sink, |callback| callback(2, 20));
+            callback(3, 30);
+        });
+        assert_eq!(outer, [(1, 10), (3, 30)]);
+        assert_eq!(inner, [(2, 20)]);
+
+        // No sink installed: events are dropped.
+        unsafe { trampoline(4, 40) };
+        assert!(SINK.with(|sink| sink.get()).is_null());
+    }
+
+    #[test]
+    fn panic_is_resumed_after_the_call() {
+        let mut seen = Vec::new();
+        let mut returned = false;
+        let result = panic::catch_unwind(AssertUnwindSafe(|| {
+            let mut sink = |_, event| {
+                seen.push(event);
+                if event == 2 {
+                    panic!("callback failed");
+                }
+            };
+            with_sink(&mut sink, |callback| unsafe {
+                callback(0, 1);
+                callback(0, 2);
+                callback(0, 3);
+                returned = true;
+            });
+        }));
+        let payload = result.unwrap_err();
+        assert_eq!(payload.downcast_ref::<&str>(), Some(&"callback failed"));
+        assert!(returned);
+        assert_eq!(seen, [1, 2]);
+        assert!(SINK.with(|sink| sink.get()).is_null());
+    }
+}
@@ -0,0 +1,112 @@
+//! Mapping between [`ParanoiaMode`] and the cdparanoia command line.
+
+use std::fmt;
+
+use crate::ParanoiaMode;
+
+/// The CLI's starting mode, before any flags.
+const CLI_DEFAULT: ParanoiaMode = ParanoiaMode::FULL.difference(ParanoiaMode::NEVERSKIP);
+
+/// A flag rejected by [`ParanoiaMode::from_cli_flags`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParseModeError {
+    flag: String,
+}
+
+impl fmt::Display for ParseModeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "unsupported paranoia flag: {}", self.flag)
+    }
+}
+
+impl std::error::Error for ParseModeError {}
+
+impl ParanoiaMode {
+    /// The cdparanoia CLI flags that select this mode, e.g. `"-Z"`, or an
+    /// empty string for the CLI's default (full paranoia, skipping allowed).
+    ///
+    /// The flags are `-Z` (`--disable-paranoia`), `-Y`
+    /// (`--disable-extra-paranoia`) and `-z` (`--never-skip`), with the
+    /// meaning the CLI gives them. Returns `None` for modes the CLI cannot
+    /// express.
+    pub fn to_cli_flags(&self) -> Option<String> {
+        let candidates = ["", "-z", "-Y", "-Y -z", "-Z", "-Z -z"];
+        candidates
+            .into_iter()
+            .find(|flags| Self::from_cli_flags(flags).as_ref() == Ok(self))
+            .map(str::to_owned)
+    }
+
+    /// Computes the mode cdparanoia would use when run with `flags`, a
+    /// whitespace-separated list of the flags described in
+    /// [`to_cli_flags`](Self::to_cli_flags).
+    ///
+    /// Flags are applied in order, starting from the CLI's default mode.
+    /// Short flags may be combined, as in `-Yz`.
+    pub fn from_cli_flags(flags: &str) -> Result<Self, ParseModeError> {
+        let mut mode = CLI_DEFAULT;
+        for token in flags.split_whitespace() {
+            let letters = match token {
+                "--disable-paranoia" => "Z",
+                "--disable-extra-paranoia" => "Y",
+                "--never-skip" => "z",
+                _ => token
+                    .strip_prefix('-')
+                    .filter(|letters| !letters.is_empty() && !letters.starts_with('-'))
+                    .ok_or_else(|| ParseModeError {
+                        flag: token.to_owned(),
+                    })?,
+            };
+            for letter in letters.chars() {
+                match letter {
+                    'Z' => mode = Self::DISABLE,
+                    'Y' => mode = (mode | Self::OVERLAP) - Self::VERIFY,
+                    'z' => mode |= Self::NEVERSKIP,
+                    _ => {
+                        return Err(ParseModeError {
+                            flag: format!("-{letter}"),
+                        })
+                    }
+                }
+            }
+        }
+        Ok(mode)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_cli_flags() {
+        assert_eq!(ParanoiaMode::from_cli_flags(""), Ok(CLI_DEFAULT));
+        assert_eq!(
+            ParanoiaMode::from_cli_flags("-Z"),
+            Ok(ParanoiaMode::DISABLE)
+        );
+        assert_eq!(
+            ParanoiaMode::from_cli_flags("--never-skip"),
+            Ok(ParanoiaMode::FULL)
+        );
+        let overlap_only = ParanoiaMode::from_cli_flags("-Yz").unwrap();
+        assert!(overlap_only.contains(ParanoiaMode::OVERLAP | ParanoiaMode::NEVERSKIP));
+        assert!(!overlap_only.contains(ParanoiaMode::VERIFY));
+        assert_eq!(
+            ParanoiaMode::from_cli_flags("-Y -q")
+                .unwrap_err()
+                .to_string(),
+            "unsupported paranoia flag: -q"
+        );
+    }
+
+    #[test]
+    fn formats_cli_flags() {
+        assert_eq!(ParanoiaMode::FULL.to_cli_flags().as_deref(), Some("-z"));
+        assert_eq!(CLI_DEFAULT.to_cli_flags().as_deref(), Some(""));
+        assert_eq!(ParanoiaMode::DISABLE.to_cli_flags().as_deref(), Some("-Z"));
+        let mode = ParanoiaMode::from_cli_flags("--disable-extra-paranoia").unwrap();
+        assert_eq!(mode.to_cli_flags().as_deref(), Some("-Y"));
+        assert_eq!(ParanoiaMode::VERIFY.to_cli_flags(), None);
+    }
+}
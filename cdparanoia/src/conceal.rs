@@ -0,0 +1,98 @@
+//! Concealment of unreadable regions in interleaved sample buffers.
+
+use std::ops::Range;
+
+/// How to fill in samples that could not be read from the disc.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Concealment {
+    /// Replace the region with digital silence. This keeps the damage
+    /// obvious and is the right choice for archival copies.
+    #[default]
+    Silence,
+
+    /// Repeat the last good frame before the region.
+    Hold,
+
+    /// Ramp linearly, per channel, from the last good frame before the
+    /// region to the first good frame after it.
+    LinearInterpolate,
+}
+
+impl Concealment {
+    /// Conceals the frames in `frames` of the interleaved buffer `samples`
+    /// with `channels` channels. `frames` is in frame units (one sample per
+    /// channel), not samples.
+    ///
+    /// Frames just outside the region are used as the neighbors; if the
+    /// region touches the start or end of the buffer, the missing neighbor
+    /// is replaced by the other one (or by silence if neither exists).
+    pub fn apply(self, samples: &mut [i16], channels: usize, frames: Range<usize>) {
+        assert!(channels > 0, "channel count must be non-zero");
+        let total_frames = samples.len() / channels;
+        let start = frames.start.min(total_frames);
+        let end = frames.end.min(total_frames);
+        if start >= end {
+            return;
+        }
+        let len = end - start;
+
+        for channel in 0..channels {
+            let before = start
+                .checked_sub(1)
+                .map(|frame| samples[frame * channels + channel]);
+            let after = (end < total_frames).then(|| samples[end * channels + channel]);
+
+            for i in 0..len {
+                let value = match self {
+                    Self::Silence => 0,
+                    Self::Hold => before.or(after).unwrap_or(0),
+                    Self::LinearInterpolate => match (before, after) {
+                        (Some(a), Some(b)) => {
+                            let t = (i + 1) as f64 / (len + 1) as f64;
+                            (a as f64 + (b as f64 - a as f64) * t).round() as i16
+                        }
+                        (a, b) => a.or(b).unwrap_or(0),
+                    },
+                };
+                samples[(start + i) * channels + channel] = value;
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn linear_interpolation_ramps_per_channel() {
+        // Stereo: left rises 0 -> 100, right falls 100 -> -100.
+        let mut samples = vec![0, 100, 7, 7, 7, 7, 7, 7, 7, 7, 100, -100];
+        Concealment::LinearInterpolate.apply(&mut samples, 2, 1..5);
+
+        let left: Vec<i16> = samples.iter().step_by(2).copied().collect();
+        let right: Vec<i16> = samples.iter().skip(1).step_by(2).copied().collect();
+        assert_eq!(left, [0, 20, 40, 60, 80, 100]);
+        assert_eq!(right, [100, 60, 20, -20, -60, -100]);
+        assert!(left.windows(2).all(|w| w[0] < w[1]));
+        assert!(right.windows(2).all(|w| w[0] > w[1]));
+    }
+
+    #[test]
+    fn hold_and_silence() {
+        let mut held = vec![5, -5, 9, 9, 9, 9, 1, 1];
+        Concealment::Hold.apply(&mut held, 2, 1..3);
+        assert_eq!(held, [5, -5, 5, -5, 5, -5, 1, 1]);
+
+        let mut silenced = vec![5, -5, 9, 9, 9, 9, 1, 1];
+        Concealment::Silence.apply(&mut silenced, 2, 1..3);
+        assert_eq!(silenced, [5, -5, 0, 0, 0, 0, 1, 1]);
+    }
+
+    #[test]
+    fn region_at_buffer_edge() {
+        let mut samples = vec![9, 9, 9, 9, 3, 4];
+        Concealment::LinearInterpolate.apply(&mut samples, 2, 0..2);
+        assert_eq!(samples, [3, 4, 3, 4, 3, 4]);
+    }
+}
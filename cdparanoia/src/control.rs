@@ -0,0 +1,863 @@
+//! Drive control through the Linux CD-ROM ioctl interface.
+
+use std::{
+    ffi::{c_int, c_uint, c_void},
+    ops::RangeInclusive,
+    os::fd::{AsRawFd, BorrowedFd, RawFd},
+    ptr::null_mut,
+};
+
+use libc::c_ulong;
+
+use crate::{
+    format::Msf, CdromDrive, Error, ErrorCode, Interface, CD_FRAMESIZE_RAW, CD_FRAMEWORDS,
+};
+
+// From <linux/cdrom.h>.
+const CDROMSTOP: c_ulong = 0x5308;
+const CDROMSTART: c_ulong = 0x5309;
+const CDROM_MEDIA_CHANGED: c_ulong = 0x5325;
+const CDSL_CURRENT: c_int = c_int::MAX;
+const CDROM_SEND_PACKET: c_ulong = 0x5393;
+const CGC_DATA_READ: u8 = 2;
+/// Size of `struct request_sense`.
+const SENSE_LEN: usize = 64;
+const SENSE_KEY_ILLEGAL_REQUEST: u8 = 0x05;
+
+/// `struct cdrom_generic_command`.
+#[repr(C)]
+struct GenericCommand {
+    cmd: [u8; 12],
+    buffer: *mut u8,
+    buflen: c_uint,
+    stat: c_int,
+    sense: *mut c_void,
+    data_direction: u8,
+    quiet: c_int,
+    timeout: c_int,
+    reserved: [*mut c_void; 1],
+}
+
+// MMC commands and GET CONFIGURATION profiles.
+const INQUIRY: u8 = 0x12;
+const GET_CONFIGURATION: u8 = 0x46;
+const READ_SUB_CHANNEL: u8 = 0x42;
+const READ_TOC_PMA_ATIP: u8 = 0x43;
+const READ_CD: u8 = 0xbe;
+const MODE_SENSE_10: u8 = 0x5a;
+const PAGE_CAPABILITIES: u8 = 0x2a;
+/// Room for the mode parameter header and the capabilities page.
+const CAPABILITIES_LEN: usize = 64;
+const TOC_FORMAT_ATIP: u8 = 0x04;
+const PROFILE_CD_ROM: u16 = 0x0008;
+const PROFILE_CD_R: u16 = 0x0009;
+const PROFILE_CD_RW: u16 = 0x000a;
+const SUB_Q_MCN: u8 = 0x02;
+const SUB_Q_ISRC: u8 = 0x03;
+/// Size of the READ SUB-CHANNEL response for MCN and ISRC.
+const SUB_Q_RESPONSE_LEN: usize = 24;
+/// Size of one sector's P-W sub-channel data.
+const SUBCHANNEL_LEN: usize = 96;
+/// Sectors fetched per READ CD command.
+const READ_CD_SECTORS: u64 = 8;
+
+/// Kind of disc in the drive, see [`CdromDrive::media_type`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MediaType {
+    /// A pressed (read-only) disc.
+    Pressed,
+    CdR,
+    CdRw,
+    /// The drive did not say.
+    Unknown,
+}
+
+/// A caveat about a drive that a ripping tool should show before
+/// starting, see [`CdromDrive::warns`].
+///
+/// Each variant is a stable identifier; the `Display` text is for people.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, displaydoc::Display)]
+#[non_exhaustive]
+pub enum DriveWarning {
+    /// drive does not report accurate audio streaming, so reads may start at the wrong position and rely on jitter correction
+    NoAccurateStream,
+
+    /// drive is read through the cooked ioctl interface, which reports read errors less precisely than SCSI
+    CookedIoctl,
+
+    /// drive is read through a SCSI transport with known kernel bugs
+    BuggyTransport,
+}
+
+/// Standard INQUIRY response length, up to the product revision.
+const INQUIRY_LEN: usize = 36;
+
+/// Drive identification from a SCSI INQUIRY, see [`CdromDrive::inquiry`].
+///
+/// The fields are ASCII with the space padding trimmed.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Inquiry {
+    /// Vendor, e.g. `PLEXTOR`.
+    pub vendor: String,
+    /// Product, e.g. `DVDR   PX-712A`.
+    pub product: String,
+    /// Firmware revision, e.g. `1.04`.
+    pub revision: String,
+}
+
+/// Size of the READ TOC/PMA/ATIP response up to the lead-out start time.
+const ATIP_LEN: usize = 16;
+
+/// The ATIP (absolute time in pregroove) of a recordable disc, see
+/// [`CdromDrive::atip`]. Times are Red Book `(minutes, seconds, frames)`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Atip {
+    /// The lead-in start time with the frame rounded down to a multiple
+    /// of ten, which identifies the disc's manufacturer, e.g. 97:24.00 for
+    /// Taiyo Yuden. Look it up in a manufacturer table to name it.
+    pub manufacturer_id: Msf,
+    /// Start time of the lead-in.
+    pub start_time: Msf,
+    /// Last possible start time of the lead-out, i.e. the disc's capacity.
+    pub last_possible_start: Msf,
+}
+
+/// Identifiers stored in the disc's Q sub-channel, see
+/// [`CdromDrive::read_identifiers`].
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct DiscIdentifiers {
+    /// The media catalog number (UPC/EAN), 13 digits.
+    pub mcn: Option<String>,
+    /// The ISRC of each track, 12 characters, in track order: index 0 is
+    /// track 1. Always `None` for data tracks.
+    pub isrcs: Vec<Option<String>>,
+}
+
+impl CdromDrive {
+    /// Stops the disc motor without ejecting the disc.
+    ///
+    /// Most drives spin back up by themselves on the next read, which may
+    /// then take a few seconds; call [`start`](Self::start) to spin up ahead
+    /// of time.
+    pub fn stop(&self) -> Result<(), Error> {
+        self.cdrom_ioctl(CDROMSTOP)
+    }
+
+    /// Spins the disc motor back up after [`stop`](Self::stop).
+    pub fn start(&self) -> Result<(), Error> {
+        self.cdrom_ioctl(CDROMSTART)
+    }
+
+    /// Polls whether the disc has been changed since the last call.
+    ///
+    /// Each change is reported once. The table of contents read at open
+    /// time is stale after a change, so the drive should then be dropped and
+    /// identified and opened again. The first call after opening may report
+    /// a change that only reflects the initial load of the disc.
+    pub fn media_changed(&self) -> Result<bool, Error> {
+        let result = self.cdrom_ioctl_arg(CDROM_MEDIA_CHANGED, CDSL_CURRENT)?;
+        Ok(result != 0)
+    }
+
+    /// Reports whether the disc is pressed or burned.
+    ///
+    /// Asks the drive for its current MMC profile, which writers report as
+    /// CD-R or CD-RW for burned media. A drive that reports a CD-ROM profile
+    /// is also asked for the disc's ATIP, which only recordable discs have.
+    /// Drives that cannot read ATIP (most non-writers) may therefore report
+    /// burned discs as [`MediaType::Pressed`]. Returns
+    /// [`MediaType::Unknown`] if the drive rejects both commands, and
+    /// [`ErrorCode::OptionNotSupported`] if the drive has no ioctl device.
+    pub fn media_type(&self) -> Result<MediaType, Error> {
+        let mut configuration = [0; 8];
+        let command = [
+            GET_CONFIGURATION,
+            0x02, // Only the current profile's header.
+            0,
+            0,
+            0,
+            0,
+            0,
+            0,
+            configuration.len() as u8,
+            0,
+            0,
+            0,
+        ];
+        let profile = match self.send_packet(command, &mut configuration) {
+            Ok(()) => Some(u16::from_be_bytes([configuration[6], configuration[7]])),
+            Err(error) if error.code() == Some(ErrorCode::OptionNotSupported) => return Err(error),
+            Err(_) => None,
+        };
+        match profile {
+            Some(PROFILE_CD_R) => return Ok(MediaType::CdR),
+            Some(PROFILE_CD_RW) => return Ok(MediaType::CdRw),
+            Some(PROFILE_CD_ROM) | None => {}
+            Some(_) => return Ok(MediaType::Unknown),
+        }
+
+        let mut atip = [0; 8];
+        let command = [
+            READ_TOC_PMA_ATIP,
+            0,
+            TOC_FORMAT_ATIP,
+            0,
+            0,
+            0,
+            0,
+            0,
+            atip.len() as u8,
+            0,
+            0,
+            0,
+        ];
+        let atip = self.send_packet(command, &mut atip).ok().map(|()| atip);
+        Ok(classify_media(profile, atip.as_ref().map(|atip| &atip[..])))
+    }
+
+    /// Reads the ATIP of a recordable disc, e.g. to record the media
+    /// manufacturer in an archival log.
+    ///
+    /// Returns `None` for pressed discs, which have no ATIP, and for
+    /// drives that cannot read it (most non-writers). Returns
+    /// [`ErrorCode::OptionNotSupported`] if the drive has no ioctl device.
+    pub fn atip(&self) -> Result<Option<Atip>, Error> {
+        let mut response = [0; ATIP_LEN];
+        let command = [
+            READ_TOC_PMA_ATIP,
+            0x02, // MSF addresses.
+            TOC_FORMAT_ATIP,
+            0,
+            0,
+            0,
+            0,
+            0,
+            ATIP_LEN as u8,
+            0,
+            0,
+            0,
+        ];
+        match self.send_packet(command, &mut response) {
+            Ok(()) => Ok(parse_atip(&response)),
+            Err(error) if error.code() == Some(ErrorCode::OptionNotSupported) => Err(error),
+            Err(_) => Ok(None),
+        }
+    }
+
+    /// Asks the drive for its vendor, product and firmware revision with a
+    /// SCSI INQUIRY, e.g. to look up its read offset by exact model.
+    ///
+    /// Returns [`ErrorCode::OptionNotSupported`] if the drive has no ioctl
+    /// device to pass the command through, and
+    /// [`ErrorCode::UnknownReadError`] if the drive rejects it.
+    pub fn inquiry(&self) -> Result<Inquiry, Error> {
+        let mut response = [0; INQUIRY_LEN];
+        let command = [INQUIRY, 0, 0, 0, INQUIRY_LEN as u8, 0, 0, 0, 0, 0, 0, 0];
+        self.send_packet(command, &mut response)?;
+        Ok(parse_inquiry(&response))
+    }
+
+    /// Whether the drive reports that it can position audio reads exactly
+    /// (the "CD-DA stream is accurate" capability), so that reads need no
+    /// jitter correction.
+    ///
+    /// Returns [`ErrorCode::OptionNotSupported`] if the drive has no ioctl
+    /// device to pass the command through, and
+    /// [`ErrorCode::UnknownReadError`] if the drive rejects it or returns
+    /// no capabilities page.
+    pub fn accurate_stream(&self) -> Result<bool, Error> {
+        let response = self.capabilities()?;
+        parse_accurate_stream(&response).ok_or(Error::from_code(ErrorCode::UnknownReadError))
+    }
+
+    /// The speed the drive currently reads at, as a multiple of 1x (176.4
+    /// kB/s), rounded to the nearest. This is what the drive settled on
+    /// after [`set_speed`](Self::set_speed), which may be lower than asked
+    /// for.
+    ///
+    /// Returns `None` if the drive cannot be asked or does not say; the
+    /// field is optional in newer MMC revisions and some drives report 0.
+    pub fn speed(&self) -> Option<i32> {
+        parse_read_speeds(&self.capabilities().ok()?)?.0
+    }
+
+    /// The fastest speed the drive can read at, in the units of
+    /// [`speed`](Self::speed).
+    pub fn max_speed(&self) -> Option<i32> {
+        parse_read_speeds(&self.capabilities().ok()?)?.1
+    }
+
+    /// Sends MODE SENSE(10) for the capabilities page.
+    fn capabilities(&self) -> Result<[u8; CAPABILITIES_LEN], Error> {
+        let mut response = [0; CAPABILITIES_LEN];
+        let command = [
+            MODE_SENSE_10,
+            0x08, // No block descriptors.
+            PAGE_CAPABILITIES,
+            0,
+            0,
+            0,
+            0,
+            0,
+            CAPABILITIES_LEN as u8,
+            0,
+            0,
+            0,
+        ];
+        self.send_packet(command, &mut response)?;
+        Ok(response)
+    }
+
+    /// Caveats about the drive and how it is accessed, for a ripping tool
+    /// to show before starting. An empty list means nothing is known to be
+    /// wrong.
+    ///
+    /// A drive that cannot be asked whether its stream is accurate gets
+    /// [`DriveWarning::NoAccurateStream`] too. Whether the drive caches
+    /// audio, and its read offset, cannot be detected and are not covered.
+    pub fn warns(&self) -> Vec<DriveWarning> {
+        let mut warnings = Vec::new();
+        if !matches!(self.accurate_stream(), Ok(true)) {
+            warnings.push(DriveWarning::NoAccurateStream);
+        }
+        match self.interface() {
+            Some(Interface::CookedIoctl) => warnings.push(DriveWarning::CookedIoctl),
+            Some(Interface::SgioScsiBuggy) => warnings.push(DriveWarning::BuggyTransport),
+            _ => {}
+        }
+        warnings
+    }
+
+    /// Reads the disc's media catalog number and the ISRC of every audio
+    /// track.
+    ///
+    /// The MCN is read once, then the tracks' ISRCs in ascending order, so
+    /// the drive moves across the disc in a single pass. Identifiers the
+    /// disc does not have, or that the drive fails to read, are `None`.
+    ///
+    /// Returns [`ErrorCode::OptionNotSupported`] if the drive has no ioctl
+    /// device to send the commands through.
+    pub fn read_identifiers(&self) -> Result<DiscIdentifiers, Error> {
+        let mcn = match self.read_sub_q(SUB_Q_MCN, 0) {
+            Ok(response) => parse_mcn(&response),
+            Err(error) if error.code() == Some(ErrorCode::OptionNotSupported) => return Err(error),
+            Err(_) => None,
+        };
+
+        let tracks = self.tracks()?;
+        let mut isrcs = Vec::with_capacity(tracks as usize);
+        for track in 1..=tracks {
+            if !self.track_audiop(track)? {
+                isrcs.push(None);
+                continue;
+            }
+            // The command takes the number stored in the TOC, which
+            // `track_*` indices need not match.
+            let number = unsafe { (*self.as_raw()).disc_toc[track as usize - 1].bTrack };
+            let isrc = self
+                .read_sub_q(SUB_Q_ISRC, number)
+                .ok()
+                .and_then(|response| parse_isrc(&response));
+            isrcs.push(isrc);
+        }
+        Ok(DiscIdentifiers { mcn, isrcs })
+    }
+
+    /// Reads the audio sectors in `range` (absolute sectors, inclusive)
+    /// together with their sub-channel data, calling `f` with the samples
+    /// and sub-channel block of each sector in order.
+    ///
+    /// The block is deinterleaved into 12 bytes for each of the P, Q, R, S,
+    /// T, U, V and W channels, in that order: the Q channel, with the fine
+    /// position, is `block[12..24]`, and the R-W channels carrying CD+G
+    /// graphics are `block[24..]`. The sectors are read straight from the
+    /// drive, without the paranoia engine's verification.
+    ///
+    /// Returns [`ErrorCode::TrackNotAudio`] if any part of the range lies in
+    /// a data track, [`ErrorCode::OptionNotSupported`] if the drive has no
+    /// ioctl device or rejects READ CD with sub-channel data, and
+    /// [`ErrorCode::UnknownReadError`] if a read fails.
+    pub fn read_with_subchannel<F>(&self, range: RangeInclusive<u64>, mut f: F) -> Result<(), Error>
+    where
+        F: FnMut(&[i16; CD_FRAMEWORDS as usize], &[u8; SUBCHANNEL_LEN]),
+    {
+        const SECTOR_LEN: usize = CD_FRAMESIZE_RAW as usize + SUBCHANNEL_LEN;
+
+        let (first_sector, last_sector) = range.into_inner();
+        if first_sector > last_sector {
+            return Ok(());
+        }
+        self.check_audio(first_sector, last_sector)?;
+        let big_endian = self.is_big_endian();
+
+        let mut buffer = vec![0; READ_CD_SECTORS as usize * SECTOR_LEN];
+        let mut sector = first_sector;
+        while sector <= last_sector {
+            let count = READ_CD_SECTORS.min(last_sector - sector + 1);
+            let lba = u32::try_from(sector).unwrap().to_be_bytes();
+            let length = (count as u32).to_be_bytes();
+            let command = [
+                READ_CD, 0x04, // CD-DA sectors only.
+                lba[0], lba[1], lba[2], lba[3], length[1], length[2], length[3],
+                0x10, // User data.
+                0x01, // Raw P-W sub-channel.
+                0,
+            ];
+            let buffer = &mut buffer[..count as usize * SECTOR_LEN];
+            let mut sense = [0; SENSE_LEN];
+            if let Err(error) = self.send_packet_sensed(command, buffer, &mut sense) {
+                if sense[2] & 0x0f == SENSE_KEY_ILLEGAL_REQUEST {
+                    return Err(Error::from_code(ErrorCode::OptionNotSupported));
+                }
+                return Err(error);
+            }
+
+            for raw in buffer.chunks_exact(SECTOR_LEN) {
+                let (audio, subchannel) = raw.split_at(CD_FRAMESIZE_RAW as usize);
+                let mut samples = [0; CD_FRAMEWORDS as usize];
+                for (sample, bytes) in samples.iter_mut().zip(audio.chunks_exact(2)) {
+                    let bytes = [bytes[0], bytes[1]];
+                    *sample = if big_endian {
+                        i16::from_be_bytes(bytes)
+                    } else {
+                        i16::from_le_bytes(bytes)
+                    };
+                }
+                f(
+                    &samples,
+                    &deinterleave_subchannel(subchannel.try_into().unwrap()),
+                );
+            }
+            sector += count;
+        }
+        Ok(())
+    }
+
+    /// Reads Q sub-channel data in the given READ SUB-CHANNEL format.
+    fn read_sub_q(&self, format: u8, track: u8) -> Result<[u8; SUB_Q_RESPONSE_LEN], Error> {
+        let mut response = [0; SUB_Q_RESPONSE_LEN];
+        let command = [
+            READ_SUB_CHANNEL,
+            0,
+            0x40, // Return sub-channel data.
+            format,
+            0,
+            0,
+            track,
+            0,
+            SUB_Q_RESPONSE_LEN as u8,
+            0,
+            0,
+            0,
+        ];
+        self.send_packet(command, &mut response)?;
+        Ok(response)
+    }
+
+    /// Sends an MMC command that reads into `buffer`.
+    fn send_packet(&self, cmd: [u8; 12], buffer: &mut [u8]) -> Result<(), Error> {
+        self.send_packet_sensed(cmd, buffer, &mut [0; SENSE_LEN])
+    }
+
+    /// Like [`send_packet`](Self::send_packet), also collecting the sense
+    /// data of a failed command.
+    fn send_packet_sensed(
+        &self,
+        cmd: [u8; 12],
+        buffer: &mut [u8],
+        sense: &mut [u8; SENSE_LEN],
+    ) -> Result<(), Error> {
+        let fd: c_int = unsafe { (*self.as_raw()).ioctl_fd };
+        if fd < 0 {
+            return Err(Error::from_code(ErrorCode::OptionNotSupported));
+        }
+        let mut command = GenericCommand {
+            cmd,
+            buffer: buffer.as_mut_ptr(),
+            buflen: buffer.len().try_into().unwrap(),
+            stat: 0,
+            sense: sense.as_mut_ptr().cast(),
+            data_direction: CGC_DATA_READ,
+            quiet: 1,
+            timeout: 0,
+            reserved: [null_mut()],
+        };
+        let result = unsafe { libc::ioctl(fd, CDROM_SEND_PACKET as _, &mut command) };
+        if result < 0 {
+            return Err(Error::from_code(ErrorCode::UnknownReadError));
+        }
+        Ok(())
+    }
+
+    /// Runs `f` while holding an exclusive `flock` on the drive's block
+    /// device, so that other processes taking the same lock, such as a
+    /// second ripper, cannot use the drive meanwhile.
+    ///
+    /// The lock is advisory: it only keeps out processes that take it too,
+    /// and does not stop anything that opens the device without locking.
+    /// It is released when `f` returns or panics.
+    ///
+    /// Returns [`ErrorCode::PermissionDenied`] without running `f` if
+    /// another process holds the lock, and
+    /// [`ErrorCode::OptionNotSupported`] if the drive has no device open or
+    /// the lock cannot be taken.
+    pub fn with_lock<F, R>(&self, f: F) -> Result<R, Error>
+    where
+        F: FnOnce() -> R,
+    {
+        let fd = self.as_raw_fd();
+        if fd < 0 {
+            return Err(Error::from_code(ErrorCode::OptionNotSupported));
+        }
+        if unsafe { libc::flock(fd, libc::LOCK_EX | libc::LOCK_NB) } < 0 {
+            let code = match std::io::Error::last_os_error().raw_os_error() {
+                Some(libc::EWOULDBLOCK) => ErrorCode::PermissionDenied,
+                _ => ErrorCode::OptionNotSupported,
+            };
+            return Err(Error::from_code(code));
+        }
+
+        struct Unlock(c_int);
+
+        impl Drop for Unlock {
+            fn drop(&mut self) {
+                unsafe { libc::flock(self.0, libc::LOCK_UN) };
+            }
+        }
+
+        let _unlock = Unlock(fd);
+        Ok(f())
+    }
+
+    /// The drive's device file descriptor, see [`as_raw_fd`](Self::as_raw_fd),
+    /// or `None` if it has none open.
+    pub fn device_fd(&self) -> Option<BorrowedFd<'_>> {
+        let fd = self.as_raw_fd();
+        // The drive keeps the descriptor open until it is dropped.
+        (fd >= 0).then(|| unsafe { BorrowedFd::borrow_raw(fd) })
+    }
+
+    /// Issues an argument-less ioctl on the drive's block device.
+    fn cdrom_ioctl(&self, request: c_ulong) -> Result<(), Error> {
+        self.cdrom_ioctl_arg(request, 0).map(drop)
+    }
+
+    fn cdrom_ioctl_arg(&self, request: c_ulong, arg: c_int) -> Result<c_int, Error> {
+        let fd: c_int = unsafe { (*self.as_raw()).ioctl_fd };
+        if fd < 0 {
+            return Err(Error::from_code(ErrorCode::OptionNotSupported));
+        }
+        let result = unsafe { libc::ioctl(fd, request as _, arg) };
+        if result < 0 {
+            return Err(Error::from_code(ErrorCode::OptionNotSupported));
+        }
+        Ok(result)
+    }
+}
+
+/// The descriptor of the drive's block device, where the CD-ROM ioctls
+/// go, or for interfaces without one the generic SCSI device. Negative if
+/// the drive has neither open.
+///
+/// The descriptor belongs to the drive: it must not be closed, and is only
+/// valid until the drive is dropped. Use it for ioctls of your own, such as
+/// ejecting the tray, but not for reads, which would move its file
+/// position under the library.
+impl AsRawFd for CdromDrive {
+    fn as_raw_fd(&self) -> RawFd {
+        let raw = unsafe { &*self.as_raw() };
+        if raw.ioctl_fd >= 0 {
+            raw.ioctl_fd
+        } else {
+            raw.cdda_fd
+        }
+    }
+}
+
+/// Combines the current profile (if the drive reported one) and the ATIP
+/// response (if the disc has one) into a media type.
+fn classify_media(profile: Option<u16>, atip: Option<&[u8]>) -> MediaType {
+    // The ATIP header is the 2-byte data length and 2 reserved bytes; the
+    // disc type is bit 6 of the third descriptor byte.
+    let atip = atip.filter(|atip| u16::from_be_bytes([atip[0], atip[1]]) >= 6);
+    match (profile, atip) {
+        (_, Some(atip)) if atip[6] & 0x40 != 0 => MediaType::CdRw,
+        (_, Some(_)) => MediaType::CdR,
+        (Some(PROFILE_CD_ROM), None) => MediaType::Pressed,
+        _ => MediaType::Unknown,
+    }
+}
+
+/// Extracts the times from a READ TOC/PMA/ATIP response, if it holds a
+/// descriptor long enough to contain them.
+fn parse_atip(response: &[u8; ATIP_LEN]) -> Option<Atip> {
+    // The data length counts everything after itself, up to byte 14 here.
+    if u16::from_be_bytes([response[0], response[1]]) < 13 {
+        return None;
+    }
+    let msf = |bytes: &[u8]| Msf {
+        minute: bytes[0],
+        second: bytes[1],
+        frame: bytes[2],
+    };
+    let start_time = msf(&response[8..11]);
+    Some(Atip {
+        manufacturer_id: Msf {
+            frame: start_time.frame / 10 * 10,
+            ..start_time
+        },
+        start_time,
+        last_possible_start: msf(&response[12..15]),
+    })
+}
+
+fn parse_inquiry(response: &[u8; INQUIRY_LEN]) -> Inquiry {
+    let field = |bytes: &[u8]| {
+        let text: String = bytes
+            .iter()
+            .map(|&b| if b.is_ascii_graphic() { b as char } else { ' ' })
+            .collect();
+        text.trim().to_owned()
+    };
+    Inquiry {
+        vendor: field(&response[8..16]),
+        product: field(&response[16..32]),
+        revision: field(&response[32..36]),
+    }
+}
+
+/// Extracts the MCN from a READ SUB-CHANNEL response, if it is marked
+/// valid and is all digits. An all-zero MCN means the disc has none.
+fn parse_mcn(response: &[u8; SUB_Q_RESPONSE_LEN]) -> Option<String> {
+    let valid = response[8] & 0x80 != 0;
+    let mcn = &response[9..22];
+    (valid && mcn.iter().all(u8::is_ascii_digit) && mcn.iter().any(|&b| b != b'0'))
+        .then(|| String::from_utf8(mcn.to_vec()).unwrap())
+}
+
+/// Extracts the ISRC from a READ SUB-CHANNEL response, if it is marked
+/// valid and well-formed.
+fn parse_isrc(response: &[u8; SUB_Q_RESPONSE_LEN]) -> Option<String> {
+    let valid = response[8] & 0x80 != 0;
+    let isrc = &response[9..21];
+    // Country and registrant codes are alphanumeric, the rest digits.
+    let well_formed = isrc[..5].iter().all(u8::is_ascii_alphanumeric)
+        && isrc[5..].iter().all(u8::is_ascii_digit)
+        && isrc.iter().any(|&b| b != b'0');
+    (valid && well_formed).then(|| String::from_utf8(isrc.to_vec()).unwrap())
+}
+
+/// Extracts the accurate-stream bit from a MODE SENSE(10) response for
+/// the capabilities page, if the response holds that page.
+fn parse_accurate_stream(response: &[u8; CAPABILITIES_LEN]) -> Option<bool> {
+    let page = capabilities_page(response)?;
+    (page.len() > 5).then(|| page[5] & 0x02 != 0)
+}
+
+/// Extracts the current and maximum read speeds, as multiples of 1x, from
+/// a MODE SENSE(10) response for the capabilities page. Speeds reported as
+/// 0 are `None`.
+fn parse_read_speeds(response: &[u8; CAPABILITIES_LEN]) -> Option<(Option<i32>, Option<i32>)> {
+    let page = capabilities_page(response)?;
+    if page.len() < 16 {
+        return None;
+    }
+    let speed = |offset: usize| {
+        let kilobytes = i32::from(u16::from_be_bytes([page[offset], page[offset + 1]]));
+        // 1x is 176.4 kB/s; drives report multiples of 176 or 177.
+        (kilobytes > 0).then(|| ((kilobytes + 88) / 176).max(1))
+    };
+    Some((speed(14), speed(8)))
+}
+
+/// The capabilities page of a MODE SENSE(10) response, after any block
+/// descriptors.
+fn capabilities_page(response: &[u8; CAPABILITIES_LEN]) -> Option<&[u8]> {
+    let block_descriptors = usize::from(u16::from_be_bytes([response[6], response[7]]));
+    let page = response.get(8 + block_descriptors..)?;
+    (!page.is_empty() && page[0] & 0x3f == PAGE_CAPABILITIES).then_some(page)
+}
+
+/// Splits raw P-W sub-channel data, which carries one bit of each channel
+/// per byte, into 12 bytes per channel.
+fn deinterleave_subchannel(raw: &[u8; SUBCHANNEL_LEN]) -> [u8; SUBCHANNEL_LEN] {
+    let mut channels = [0; SUBCHANNEL_LEN];
+    for (index, &byte) in raw.iter().enumerate() {
+        let (channel_byte, bit) = (index / 8, index % 8);
+        for channel in 0..8 {
+            if byte & (0x80 >> channel) != 0 {
+                channels[channel * 12 + channel_byte] |= 0x80 >> bit;
+            }
+        }
+    }
+    channels
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn lock_excludes_other_holders() {
+        use std::{fs::File, os::fd::AsRawFd};
+
+        use crate::testing::FakeDrive;
+
+        let path = std::env::temp_dir().join(format!("cdparanoia-lock-{}", std::process::id()));
+        let (first, second) = (File::create(&path).unwrap(), File::open(&path).unwrap());
+        let ours = FakeDrive::new(&[(0, 0)], 100);
+        let theirs = FakeDrive::new(&[(0, 0)], 100);
+        unsafe { (*ours.drive().as_raw()).ioctl_fd = first.as_raw_fd() };
+        unsafe { (*theirs.drive().as_raw()).ioctl_fd = second.as_raw_fd() };
+
+        let blocked = ours
+            .drive()
+            .with_lock(|| theirs.drive().with_lock(|| ()).unwrap_err())
+            .unwrap();
+        assert_eq!(blocked.code(), Some(ErrorCode::PermissionDenied));
+        // Released again.
+        assert_eq!(theirs.drive().with_lock(|| 7), Ok(7));
+        std::fs::remove_file(path).unwrap();
+    }
+
+    #[test]
+    fn exposes_device_fd() {
+        use crate::testing::FakeDrive;
+
+        let fake = FakeDrive::new(&[(0, 0)], 100);
+        assert!(fake.drive().device_fd().is_none());
+        unsafe { (*fake.drive().as_raw()).cdda_fd = 7 };
+        assert_eq!(fake.drive().as_raw_fd(), 7);
+        unsafe { (*fake.drive().as_raw()).ioctl_fd = 9 };
+        assert_eq!(fake.drive().device_fd().map(|fd| fd.as_raw_fd()), Some(9));
+    }
+
+    #[test]
+    fn parses_inquiry() {
+        let mut response = [0; INQUIRY_LEN];
+        response[8..].copy_from_slice(b"PLEXTOR DVDR   PX-712A  1.04");
+        assert_eq!(
+            parse_inquiry(&response),
+            Inquiry {
+                vendor: "PLEXTOR".into(),
+                product: "DVDR   PX-712A".into(),
+                revision: "1.04".into(),
+            }
+        );
+    }
+
+    #[test]
+    fn parses_identifiers() {
+        let response = |valid: bool, text: &[u8]| {
+            let mut response = [0; SUB_Q_RESPONSE_LEN];
+            response[8] = if valid { 0x80 } else { 0 };
+            response[9..9 + text.len()].copy_from_slice(text);
+            response
+        };
+        assert_eq!(
+            parse_mcn(&response(true, b"0724384960650")).as_deref(),
+            Some("0724384960650")
+        );
+        assert_eq!(parse_mcn(&response(false, b"0724384960650")), None);
+        assert_eq!(parse_mcn(&response(true, b"0000000000000")), None);
+        assert_eq!(
+            parse_isrc(&response(true, b"USEE10001992")).as_deref(),
+            Some("USEE10001992")
+        );
+        assert_eq!(parse_isrc(&response(true, b"USEE1000199X")), None);
+        assert_eq!(parse_isrc(&response(false, b"USEE10001992")), None);
+    }
+
+    #[test]
+    fn parses_atip() {
+        let mut response = [0; ATIP_LEN];
+        response[1] = 22;
+        response[8..11].copy_from_slice(&[97, 24, 1]);
+        response[12..15].copy_from_slice(&[79, 59, 74]);
+        assert_eq!(
+            parse_atip(&response),
+            Some(Atip {
+                manufacturer_id: Msf {
+                    minute: 97,
+                    second: 24,
+                    frame: 0
+                },
+                start_time: Msf {
+                    minute: 97,
+                    second: 24,
+                    frame: 1
+                },
+                last_possible_start: Msf {
+                    minute: 79,
+                    second: 59,
+                    frame: 74
+                },
+            })
+        );
+        response[1] = 2;
+        assert_eq!(parse_atip(&response), None);
+    }
+
+    #[test]
+    fn classifies_media() {
+        let atip = |disc_type: u8| [0, 6, 0, 0, 0x80, 0x80, disc_type, 0];
+        assert_eq!(
+            classify_media(Some(PROFILE_CD_ROM), None),
+            MediaType::Pressed
+        );
+        assert_eq!(
+            classify_media(Some(PROFILE_CD_ROM), Some(&atip(0x00))),
+            MediaType::CdR
+        );
+        assert_eq!(classify_media(None, Some(&atip(0x40))), MediaType::CdRw);
+        assert_eq!(classify_media(None, None), MediaType::Unknown);
+        // A response without a descriptor is no ATIP.
+        assert_eq!(
+            classify_media(Some(PROFILE_CD_ROM), Some(&[0, 2, 0, 0, 0, 0, 0, 0])),
+            MediaType::Pressed
+        );
+    }
+
+    #[test]
+    fn parses_accurate_stream() {
+        let mut response = [0; CAPABILITIES_LEN];
+        response[8] = PAGE_CAPABILITIES;
+        assert_eq!(parse_accurate_stream(&response), Some(false));
+        response[13] = 0x02;
+        assert_eq!(parse_accurate_stream(&response), Some(true));
+        // The page follows any block descriptors.
+        response[7] = 8;
+        assert_eq!(parse_accurate_stream(&response), None);
+        response[16] = PAGE_CAPABILITIES;
+        response[21] = 0x03;
+        assert_eq!(parse_accurate_stream(&response), Some(true));
+    }
+
+    #[test]
+    fn parses_read_speeds() {
+        let mut response = [0; CAPABILITIES_LEN];
+        assert_eq!(parse_read_speeds(&response), None);
+        response[8] = PAGE_CAPABILITIES;
+        assert_eq!(parse_read_speeds(&response), Some((None, None)));
+        // Maximum 48x, current 24x.
+        response[16..18].copy_from_slice(&8467u16.to_be_bytes());
+        response[22..24].copy_from_slice(&4234u16.to_be_bytes());
+        assert_eq!(parse_read_speeds(&response), Some((Some(24), Some(48))));
+    }
+
+    #[test]
+    fn deinterleaves_subchannel() {
+        let mut raw = [0x40; SUBCHANNEL_LEN];
+        raw[9] |= 0x80;
+        raw[95] |= 0x01;
+        let channels = deinterleave_subchannel(&raw);
+        assert_eq!(channels[1], 0x40);
+        assert!(channels[..12]
+            .iter()
+            .enumerate()
+            .all(|(i, &b)| i == 1 || b == 0));
+        assert!(channels[12..24].iter().all(|&b| b == 0xff));
+        assert!(channels[24..95].iter().all(|&b| b == 0));
+        assert_eq!(channels[95], 0x01);
+    }
+}
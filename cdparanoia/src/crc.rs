@@ -0,0 +1,78 @@
+//! CRC-32 (IEEE 802.3) over ripped sample data.
+
+const TABLE: [u32; 256] = {
+    let mut table = [0; 256];
+    let mut i = 0;
+    while i < 256 {
+        let mut value = i as u32;
+        let mut bit = 0;
+        while bit < 8 {
+            value = if value & 1 != 0 {
+                (value >> 1) ^ 0xedb88320
+            } else {
+                value >> 1
+            };
+            bit += 1;
+        }
+        table[i] = value;
+        i += 1;
+    }
+    table
+};
+
+/// Streaming CRC-32 hasher, using the same polynomial as zlib and EAC's
+/// "Copy CRC".
+#[derive(Debug, Clone)]
+pub struct Crc32 {
+    state: u32,
+}
+
+impl Crc32 {
+    pub fn new() -> Self {
+        Self { state: !0 }
+    }
+
+    pub fn update(&mut self, bytes: &[u8]) {
+        for &byte in bytes {
+            self.state = TABLE[((self.state ^ byte as u32) & 0xff) as usize] ^ (self.state >> 8);
+        }
+    }
+
+    /// Feeds samples as little-endian bytes, independent of host endianness.
+    pub fn update_samples(&mut self, samples: &[i16]) {
+        for sample in samples {
+            self.update(&sample.to_le_bytes());
+        }
+    }
+
+    pub fn finish(&self) -> u32 {
+        !self.state
+    }
+}
+
+impl Default for Crc32 {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn check_value() {
+        let mut crc = Crc32::new();
+        crc.update(b"123456789");
+        assert_eq!(crc.finish(), 0xcbf43926);
+    }
+
+    #[test]
+    fn samples_are_little_endian() {
+        let mut bytes = Crc32::new();
+        bytes.update(&[0x34, 0x12, 0xff, 0xff]);
+        let mut samples = Crc32::new();
+        samples.update_samples(&[0x1234, -1]);
+        assert_eq!(bytes.finish(), samples.finish());
+    }
+}
@@ -0,0 +1,117 @@
+//! Decoding of the events passed to read callbacks.
+
+use std::{ffi::c_int, fmt};
+
+/// An event reported by the paranoia engine during a read, decoded from its
+/// `PARANOIA_CB_*` code.
+///
+/// The position passed along with an event is in samples from the start of
+/// the disc, except for [`Overlap`](Self::Overlap).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum ParanoiaEvent {
+    Read,
+    Verify,
+    FixupEdge,
+    FixupAtom,
+    Scratch,
+    Repair,
+    Skip,
+    Drift,
+    Backoff,
+    /// The engine changed its dynamic overlap; the position is the new
+    /// overlap in samples.
+    Overlap,
+    FixupDropped,
+    FixupDuped,
+    ReadError,
+    CacheError,
+    /// A code this version does not know, e.g. from a newer library.
+    Unknown(c_int),
+}
+
+impl ParanoiaEvent {
+    pub fn from_raw(event: c_int) -> Self {
+        use cdparanoia_sys::*;
+        let Ok(code) = u32::try_from(event) else {
+            return Self::Unknown(event);
+        };
+        match code {
+            PARANOIA_CB_READ => Self::Read,
+            PARANOIA_CB_VERIFY => Self::Verify,
+            PARANOIA_CB_FIXUP_EDGE => Self::FixupEdge,
+            PARANOIA_CB_FIXUP_ATOM => Self::FixupAtom,
+            PARANOIA_CB_SCRATCH => Self::Scratch,
+            PARANOIA_CB_REPAIR => Self::Repair,
+            PARANOIA_CB_SKIP => Self::Skip,
+            PARANOIA_CB_DRIFT => Self::Drift,
+            PARANOIA_CB_BACKOFF => Self::Backoff,
+            PARANOIA_CB_OVERLAP => Self::Overlap,
+            PARANOIA_CB_FIXUP_DROPPED => Self::FixupDropped,
+            PARANOIA_CB_FIXUP_DUPED => Self::FixupDuped,
+            PARANOIA_CB_READERR => Self::ReadError,
+            PARANOIA_CB_CACHEERR => Self::CacheError,
+            _ => Self::Unknown(event),
+        }
+    }
+
+    /// The event's `PARANOIA_CB_*` code.
+    pub fn to_raw(self) -> c_int {
+        use cdparanoia_sys::*;
+        let code = match self {
+            Self::Read => PARANOIA_CB_READ,
+            Self::Verify => PARANOIA_CB_VERIFY,
+            Self::FixupEdge => PARANOIA_CB_FIXUP_EDGE,
+            Self::FixupAtom => PARANOIA_CB_FIXUP_ATOM,
+            Self::Scratch => PARANOIA_CB_SCRATCH,
+            Self::Repair => PARANOIA_CB_REPAIR,
+            Self::Skip => PARANOIA_CB_SKIP,
+            Self::Drift => PARANOIA_CB_DRIFT,
+            Self::Backoff => PARANOIA_CB_BACKOFF,
+            Self::Overlap => PARANOIA_CB_OVERLAP,
+            Self::FixupDropped => PARANOIA_CB_FIXUP_DROPPED,
+            Self::FixupDuped => PARANOIA_CB_FIXUP_DUPED,
+            Self::ReadError => PARANOIA_CB_READERR,
+            Self::CacheError => PARANOIA_CB_CACHEERR,
+            Self::Unknown(event) => return event,
+        };
+        code as c_int
+    }
+}
+
+impl fmt::Display for ParanoiaEvent {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Self::Read => "read",
+            Self::Verify => "verifying jitter",
+            Self::FixupEdge => "fixed edge jitter",
+            Self::FixupAtom => "fixed atom jitter",
+            Self::Scratch => "scratch",
+            Self::Repair => "repair",
+            Self::Skip => "skip exhausted retry",
+            Self::Drift => "drift exhausted retry",
+            Self::Backoff => "backoff",
+            Self::Overlap => "dynamic overlap adjust",
+            Self::FixupDropped => "fixed dropped bytes",
+            Self::FixupDuped => "fixed duplicated bytes",
+            Self::ReadError => "read error",
+            Self::CacheError => "cache error",
+            Self::Unknown(_) => "unknown",
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn raw_round_trip() {
+        for code in -1..=20 {
+            assert_eq!(ParanoiaEvent::from_raw(code).to_raw(), code);
+        }
+        let skip = ParanoiaEvent::from_raw(cdparanoia_sys::PARANOIA_CB_SKIP as c_int);
+        assert_eq!(skip, ParanoiaEvent::Skip);
+        assert_eq!(skip.to_string(), "skip exhausted retry");
+        assert_eq!(ParanoiaEvent::from_raw(-1), ParanoiaEvent::Unknown(-1));
+    }
+}
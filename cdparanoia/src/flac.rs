@@ -0,0 +1,184 @@
+//! Streaming FLAC encoding of ripped audio.
+//!
+//! Encoding is done by the reference encoder, libFLAC, through the
+//! [`flac-bound`](https://crates.io/crates/flac-bound) bindings, so like
+//! libcdparanoia it must be installed on the system. Streams are 16-bit
+//! stereo 44100 Hz at libFLAC's default compression level, 5.
+
+use std::{
+    ffi::{c_int, c_long},
+    fs::File,
+    io::{self, BufWriter, SeekFrom, Write},
+    path::Path,
+};
+
+use flac_bound::FlacEncoder;
+/// Adapter giving libFLAC an [`io::Write`] to write the stream to, see
+/// [`FlacWriter::new`].
+pub use flac_bound::WriteWrapper;
+
+use crate::{CdromParanoia, Error, ErrorCode, CD_FRAMESAMPLES};
+
+const SAMPLE_RATE: u32 = 44100;
+const CHANNELS: usize = 2;
+const BITS_PER_SAMPLE: u32 = 16;
+const COMPRESSION_LEVEL: u32 = 5;
+
+/// Writes interleaved 16-bit stereo samples as a FLAC stream.
+///
+/// Samples are encoded as they are written, so memory use does not depend
+/// on the length of the stream.
+pub struct FlacWriter<'out> {
+    encoder: FlacEncoder<'out>,
+    remaining_frames: u64,
+    /// Samples widened for libFLAC, kept to reuse the allocation.
+    widened: Vec<i32>,
+}
+
+impl<'out> FlacWriter<'out> {
+    /// Starts a stream of `total_frames` frames of audio in `out`:
+    ///
+    /// ```no_run
+    /// # use cdparanoia::{FlacWriter, WriteWrapper};
+    /// let mut file = std::fs::File::create("track.flac")?;
+    /// let mut out = WriteWrapper(&mut file);
+    /// let mut writer = FlacWriter::new(&mut out, 1)?;
+    /// writer.write_samples(&[0, 0])?;
+    /// writer.finish()?;
+    /// # Ok::<(), std::io::Error>(())
+    /// ```
+    ///
+    /// Exactly that many frames must be written before calling
+    /// [`finish`](Self::finish), since the count is recorded in the stream
+    /// header before any audio.
+    pub fn new(out: &'out mut WriteWrapper<'out>, total_frames: u64) -> io::Result<Self> {
+        let encoder = FlacEncoder::new()
+            .ok_or_else(|| io::Error::new(io::ErrorKind::OutOfMemory, "cannot create encoder"))?
+            .channels(CHANNELS as u32)
+            .bits_per_sample(BITS_PER_SAMPLE)
+            .sample_rate(SAMPLE_RATE)
+            .compression_level(COMPRESSION_LEVEL)
+            .total_samples_estimate(total_frames)
+            .init_write(out)
+            .map_err(|error| io::Error::other(format!("cannot start encoder: {error:?}")))?;
+        Ok(Self {
+            encoder,
+            remaining_frames: total_frames,
+            widened: Vec::new(),
+        })
+    }
+
+    /// Appends interleaved stereo samples.
+    ///
+    /// Returns an [`io::ErrorKind::InvalidInput`] error, writing nothing,
+    /// if the samples are not whole frames or are more than the stream has
+    /// left.
+    pub fn write_samples(&mut self, samples: &[i16]) -> io::Result<()> {
+        if samples.len() % CHANNELS != 0 {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "samples must be whole frames",
+            ));
+        }
+        let frames = (samples.len() / CHANNELS) as u64;
+        if frames > self.remaining_frames {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "more samples than announced in the stream header",
+            ));
+        }
+        if frames == 0 {
+            return Ok(());
+        }
+
+        self.widened.clear();
+        self.widened
+            .extend(samples.iter().map(|&sample| i32::from(sample)));
+        self.encoder
+            .process_interleaved(&self.widened, frames as u32)
+            .map_err(|()| self.encoder_error())?;
+        self.remaining_frames -= frames;
+        Ok(())
+    }
+
+    /// Encodes what is still buffered and ends the stream.
+    pub fn finish(self) -> io::Result<()> {
+        if self.remaining_frames != 0 {
+            return Err(io::Error::new(
+                io::ErrorKind::UnexpectedEof,
+                "fewer samples than announced in the stream header",
+            ));
+        }
+        self.encoder
+            .finish()
+            .map(drop)
+            .map_err(|encoder| io::Error::other(format!("encoder failed: {:?}", encoder.state())))
+    }
+
+    fn encoder_error(&self) -> io::Error {
+        io::Error::other(format!("encoder failed: {:?}", self.encoder.state()))
+    }
+}
+
+impl CdromParanoia {
+    /// Rips an audio track straight into a FLAC file at `out`.
+    ///
+    /// Sectors are encoded as they are read, so the track is never held in
+    /// memory as a whole. Library errors are returned as [`io::Error`]s
+    /// with the kind from the [`Error`] conversion.
+    pub fn encode_track_flac(
+        &mut self,
+        track: u32,
+        out: &Path,
+        callback: extern "C" fn(c_long, c_int),
+    ) -> io::Result<()> {
+        let drive = self.drive();
+        if !drive.track_audiop(track)? {
+            return Err(Error::from_code(ErrorCode::TrackNotAudio).into());
+        }
+        let sectors = drive.track_sector_range(track)?;
+        let total_frames = (sectors.end() - sectors.start() + 1) * u64::from(CD_FRAMESAMPLES);
+
+        let mut file = BufWriter::new(File::create(out)?);
+        {
+            let mut wrapper = WriteWrapper(&mut file);
+            let mut writer = FlacWriter::new(&mut wrapper, total_frames)?;
+            self.seek(SeekFrom::Start(*sectors.start()))?;
+            for _ in sectors {
+                writer.write_samples(self.read_checked(Some(callback))?)?;
+            }
+            writer.finish()?;
+        }
+        file.flush()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn rejects_wrong_length() {
+        let mut out = Vec::new();
+        let mut wrapper = WriteWrapper(&mut out);
+        let mut writer = FlacWriter::new(&mut wrapper, 2).unwrap();
+        let error = writer.write_samples(&[0; 3]).unwrap_err();
+        assert_eq!(error.kind(), io::ErrorKind::InvalidInput);
+        let error = writer.write_samples(&[0; 6]).unwrap_err();
+        assert_eq!(error.kind(), io::ErrorKind::InvalidInput);
+        writer.write_samples(&[0; 2]).unwrap();
+        assert!(writer.finish().is_err());
+    }
+
+    #[test]
+    fn writes_stream_header() {
+        let mut out = Vec::new();
+        {
+            let mut wrapper = WriteWrapper(&mut out);
+            let mut writer = FlacWriter::new(&mut wrapper, 3).unwrap();
+            writer.write_samples(&[1, -1, 2, -2, 3, -3]).unwrap();
+            writer.finish().unwrap();
+        }
+        assert_eq!(&out[..4], b"fLaC");
+    }
+}
@@ -0,0 +1,144 @@
+//! Red Book addressing constants and conversions.
+//!
+//! Sector numbers used everywhere else in this crate are logical block
+//! addresses (LBA), which start at the first sector of track 1. Red Book MSF
+//! addresses, as printed in cue sheets and used by CDDB, start two seconds
+//! earlier, at the beginning of the mandatory pregap.
+
+use std::fmt;
+
+/// Number of sectors (frames) per second of audio.
+pub const SECTORS_PER_SECOND: u64 = 75;
+
+/// Length of the pregap before track 1, which Red Book MSF addresses count
+/// but LBAs do not.
+pub const PREGAP_SECTORS: u64 = 150;
+
+/// A minutes, seconds and frames position or length.
+///
+/// How the fields relate to a sector depends on the conversion:
+/// [`from_sector`](Self::from_sector) just splits a sector count, as for
+/// track lengths and offsets within a track, while
+/// [`from_lba`](Self::from_lba) gives the Red Book address of a sector,
+/// [`PREGAP_SECTORS`] later. Mixing them up makes track boundaries off by
+/// two seconds.
+///
+/// Displays as `MM:SS.FF`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Msf {
+    pub minute: u8,
+    /// 0 to 59.
+    pub second: u8,
+    /// 0 to 74.
+    pub frame: u8,
+}
+
+impl Msf {
+    /// Splits a sector count, without applying the pregap.
+    ///
+    /// # Panics
+    ///
+    /// If `sectors` is 256 minutes or more, see
+    /// [`checked_from_sector`](Self::checked_from_sector).
+    pub fn from_sector(sectors: u64) -> Self {
+        Self::checked_from_sector(sectors).expect("too many minutes for an MSF")
+    }
+
+    /// Like [`from_sector`](Self::from_sector), but returns `None` if
+    /// `sectors` is 256 minutes or more.
+    pub fn checked_from_sector(sectors: u64) -> Option<Self> {
+        let seconds = sectors / SECTORS_PER_SECOND;
+        Some(Self {
+            minute: u8::try_from(seconds / 60).ok()?,
+            second: (seconds % 60) as u8,
+            frame: (sectors % SECTORS_PER_SECOND) as u8,
+        })
+    }
+
+    /// Inverse of [`from_sector`](Self::from_sector).
+    pub fn to_sector(&self) -> u64 {
+        (u64::from(self.minute) * 60 + u64::from(self.second)) * SECTORS_PER_SECOND
+            + u64::from(self.frame)
+    }
+
+    /// The Red Book address of an LBA, as used by cue sheets and CDDB.
+    ///
+    /// # Panics
+    ///
+    /// If the address is 256 minutes or more, see
+    /// [`checked_from_lba`](Self::checked_from_lba).
+    pub fn from_lba(lba: u64) -> Self {
+        Self::checked_from_lba(lba).expect("too many minutes for an MSF")
+    }
+
+    /// Like [`from_lba`](Self::from_lba), but returns `None` if the
+    /// address is 256 minutes or more.
+    pub fn checked_from_lba(lba: u64) -> Option<Self> {
+        Self::checked_from_sector(lba.checked_add(PREGAP_SECTORS)?)
+    }
+
+    /// Inverse of [`from_lba`](Self::from_lba). Returns `None` for
+    /// addresses inside the pregap before track 1, which have no LBA.
+    pub fn to_lba(&self) -> Option<u64> {
+        self.to_sector().checked_sub(PREGAP_SECTORS)
+    }
+}
+
+impl fmt::Display for Msf {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{:02}:{:02}.{:02}", self.minute, self.second, self.frame)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn raw_and_redbook_differ_by_pregap() {
+        assert_eq!(Msf::from_sector(0).to_string(), "00:00.00");
+        assert_eq!(Msf::from_lba(0).to_string(), "00:02.00");
+        assert_eq!(Msf::from_lba(0).to_sector(), PREGAP_SECTORS);
+        let msf = Msf::from_lba(123_456);
+        assert_eq!(
+            Msf {
+                second: 0,
+                frame: 0,
+                ..msf
+            }
+            .to_lba(),
+            Some(27 * 60 * 75 - PREGAP_SECTORS)
+        );
+    }
+
+    #[test]
+    fn msf_conversions() {
+        let msf = Msf::from_sector(4574);
+        assert_eq!(
+            msf,
+            Msf {
+                minute: 1,
+                second: 0,
+                frame: 74
+            }
+        );
+        assert_eq!(msf.to_sector(), 4574);
+        assert_eq!(msf.to_string(), "01:00.74");
+        assert_eq!(Msf::from_lba(0).to_string(), "00:02.00");
+        assert_eq!(Msf::from_lba(4574).to_lba(), Some(4574));
+        assert_eq!(Msf::from_sector(149).to_lba(), None);
+    }
+
+    #[test]
+    fn checked_conversions_reject_overflow() {
+        let last = 256 * 60 * SECTORS_PER_SECOND - 1;
+        assert_eq!(Msf::checked_from_sector(last).unwrap().to_sector(), last);
+        assert_eq!(Msf::checked_from_sector(last + 1), None);
+        assert_eq!(
+            Msf::checked_from_lba(last - PREGAP_SECTORS),
+            Msf::checked_from_sector(last)
+        );
+        assert_eq!(Msf::checked_from_lba(last + 1 - PREGAP_SECTORS), None);
+        assert_eq!(Msf::checked_from_lba(u64::MAX), None);
+    }
+}
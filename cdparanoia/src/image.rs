@@ -0,0 +1,282 @@
+//! Reading audio from a raw disc image instead of a drive.
+
+#[cfg(feature = "test-faults")]
+use std::collections::HashMap;
+use std::{
+    fs::File,
+    io::{self, Read, Seek, SeekFrom},
+    ops::RangeInclusive,
+    path::Path,
+};
+
+use crate::{Crc32, DiscLayout, Error, ErrorCode, Samples, Track, CD_FRAMESIZE_RAW, CD_FRAMEWORDS};
+
+/// A raw audio image (a `.bin` of 2352-byte sectors of little-endian
+/// samples, starting at sector 0) together with its table of contents.
+///
+/// The read methods mirror those of [`CdromParanoia`](crate::CdromParanoia),
+/// so images can be checked or transcoded without a drive. The data is
+/// returned as stored; there is nothing for paranoia to correct.
+pub struct ImageDrive {
+    file: File,
+    layout: DiscLayout,
+    /// Injected errors by sector, with the number of reads left to fail.
+    #[cfg(feature = "test-faults")]
+    faults: HashMap<u64, (ErrorCode, u32)>,
+}
+
+impl ImageDrive {
+    /// Opens the image at `bin`, described by `layout` (for example one
+    /// parsed from the image's cue sheet).
+    ///
+    /// Fails with [`io::ErrorKind::UnexpectedEof`] if the file is too short
+    /// to hold every track in the layout.
+    pub fn open(bin: &Path, layout: &DiscLayout) -> io::Result<Self> {
+        let file = File::open(bin)?;
+        let required = layout
+            .tracks
+            .iter()
+            .map(|track| track.last_sector + 1)
+            .max()
+            .unwrap_or(0)
+            * CD_FRAMESIZE_RAW as u64;
+        if file.metadata()?.len() < required {
+            return Err(io::Error::new(
+                io::ErrorKind::UnexpectedEof,
+                "image is shorter than its table of contents",
+            ));
+        }
+        Ok(Self {
+            file,
+            layout: layout.clone(),
+            #[cfg(feature = "test-faults")]
+            faults: HashMap::new(),
+        })
+    }
+
+    pub fn layout(&self) -> &DiscLayout {
+        &self.layout
+    }
+
+    /// Makes the next `times` reads of `sector` fail with `kind` before
+    /// it reads normally again, e.g. to exercise retry or concealment
+    /// logic. Replaces any error already injected for the sector.
+    ///
+    /// Every method that reads the sector counts as a read, including
+    /// [`read_range`](Self::read_range) and [`read_track`](Self::read_track)
+    /// spanning it. Only images support this; drives never fail on
+    /// purpose.
+    #[cfg(feature = "test-faults")]
+    pub fn inject_error(&mut self, sector: u64, kind: ErrorCode, times: u32) {
+        if times == 0 {
+            self.faults.remove(&sector);
+        } else {
+            self.faults.insert(sector, (kind, times));
+        }
+    }
+
+    #[cfg(feature = "test-faults")]
+    fn take_fault(&mut self, sector: u64) -> Result<(), Error> {
+        let Some((kind, times)) = self.faults.get_mut(&sector) else {
+            return Ok(());
+        };
+        let kind = *kind;
+        *times -= 1;
+        if *times == 0 {
+            self.faults.remove(&sector);
+        }
+        Err(Error::from_code(kind))
+    }
+
+    /// Reads one sector into `buf`.
+    pub fn read_sector(
+        &mut self,
+        sector: u64,
+        buf: &mut [i16; CD_FRAMEWORDS as usize],
+    ) -> Result<(), Error> {
+        #[cfg(feature = "test-faults")]
+        self.take_fault(sector)?;
+        let mut bytes = [0; CD_FRAMESIZE_RAW as usize];
+        self.file
+            .seek(SeekFrom::Start(sector * CD_FRAMESIZE_RAW as u64))
+            .and_then(|_| self.file.read_exact(&mut bytes))
+            .map_err(|_| Error::from_code(ErrorCode::UnknownReadError))?;
+        for (sample, bytes) in buf.iter_mut().zip(bytes.chunks_exact(2)) {
+            *sample = i16::from_le_bytes([bytes[0], bytes[1]]);
+        }
+        Ok(())
+    }
+
+    /// See [`CdromParanoia::read_range`](crate::CdromParanoia::read_range).
+    pub fn read_range(&mut self, range: RangeInclusive<u64>) -> Result<Vec<i16>, Error> {
+        let (first_sector, last_sector) = range.into_inner();
+        if first_sector > last_sector {
+            return Ok(Vec::new());
+        }
+        let overlaps_data = self.layout.tracks.iter().any(|track| {
+            !track.audio && track.first_sector <= last_sector && first_sector <= track.last_sector
+        });
+        if overlaps_data {
+            return Err(Error::from_code(ErrorCode::TrackNotAudio));
+        }
+
+        let sectors = (last_sector - first_sector + 1) as usize;
+        let mut samples = vec![0; sectors * CD_FRAMEWORDS as usize];
+        for (sector, chunk) in
+            (first_sector..).zip(samples.chunks_exact_mut(CD_FRAMEWORDS as usize))
+        {
+            self.read_sector(sector, chunk.try_into().unwrap())?;
+        }
+        Ok(samples)
+    }
+
+    /// See [`CdromParanoia::read_track`](crate::CdromParanoia::read_track).
+    pub fn read_track(&mut self, track: u32) -> Result<Samples, Error> {
+        let track = self.audio_track(track)?;
+        self.read_range(track.first_sector..=track.last_sector)
+            .map(Samples::cd)
+    }
+
+    /// See [`CdromParanoia::flush`](crate::CdromParanoia::flush). Images
+    /// have no cache, so this does nothing.
+    pub fn flush(&self) -> Result<(), Error> {
+        Ok(())
+    }
+
+    /// See [`CdromParanoia::track_crc`](crate::CdromParanoia::track_crc).
+    pub fn track_crc(&mut self, track: u32) -> Result<u32, Error> {
+        let track = self.audio_track(track)?;
+        let mut crc = Crc32::new();
+        let mut buf = [0; CD_FRAMEWORDS as usize];
+        for sector in track.first_sector..=track.last_sector {
+            self.read_sector(sector, &mut buf)?;
+            crc.update_samples(&buf);
+        }
+        Ok(crc.finish())
+    }
+
+    fn audio_track(&self, track: u32) -> Result<Track, Error> {
+        let track = *self
+            .layout
+            .track(track)
+            .ok_or(Error::from_code(ErrorCode::InvalidTrackNumber))?;
+        if !track.audio {
+            return Err(Error::from_code(ErrorCode::TrackNotAudio));
+        }
+        Ok(track)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{fs, path::PathBuf};
+
+    use super::*;
+
+    fn track(number: u32, sectors: RangeInclusive<u64>, audio: bool) -> Track {
+        Track {
+            number,
+            first_sector: *sectors.start(),
+            last_sector: *sectors.end(),
+            channels: 2,
+            audio,
+            copy_permitted: false,
+            preemphasis: false,
+        }
+    }
+
+    /// Writes an image where every sample holds the index of its sector.
+    fn write_image(name: &str, sectors: u64) -> PathBuf {
+        let path =
+            std::env::temp_dir().join(format!("cdparanoia-{}-{}.bin", name, std::process::id()));
+        let mut bytes = Vec::new();
+        for sector in 0..sectors {
+            for _ in 0..CD_FRAMEWORDS {
+                bytes.extend_from_slice(&(sector as i16).to_le_bytes());
+            }
+        }
+        fs::write(&path, bytes).unwrap();
+        path
+    }
+
+    fn layout() -> DiscLayout {
+        DiscLayout {
+            tracks: vec![
+                track(1, 0..=1, true),
+                track(2, 2..=4, true),
+                track(3, 5..=6, false),
+            ],
+            lead_out: 7,
+        }
+    }
+
+    #[test]
+    fn reads_tracks_and_ranges() {
+        let path = write_image("reads", 7);
+        let mut image = ImageDrive::open(&path, &layout()).unwrap();
+
+        let samples = image.read_track(2).unwrap();
+        assert_eq!(samples.len(), 3 * CD_FRAMEWORDS as usize);
+        assert_eq!(samples[0], 2);
+        assert_eq!(*samples.last().unwrap(), 4);
+
+        let mut crc = Crc32::new();
+        crc.update_samples(&samples);
+        assert_eq!(image.track_crc(2).unwrap(), crc.finish());
+
+        assert_eq!(
+            image.read_range(1..=2).unwrap().len(),
+            2 * CD_FRAMEWORDS as usize
+        );
+        assert_eq!(
+            image.read_range(4..=5).unwrap_err().code(),
+            Some(ErrorCode::TrackNotAudio)
+        );
+        assert_eq!(
+            image.read_track(3).unwrap_err().code(),
+            Some(ErrorCode::TrackNotAudio)
+        );
+        assert_eq!(
+            image.read_track(4).unwrap_err().code(),
+            Some(ErrorCode::InvalidTrackNumber)
+        );
+        fs::remove_file(path).unwrap();
+    }
+
+    #[test]
+    fn flush_keeps_clean_data() {
+        let path = write_image("flush", 7);
+        let mut image = ImageDrive::open(&path, &layout()).unwrap();
+        let before = image.read_range(1..=3).unwrap();
+        image.flush().unwrap();
+        assert_eq!(image.read_range(1..=3).unwrap(), before);
+        fs::remove_file(path).unwrap();
+    }
+
+    #[cfg(feature = "test-faults")]
+    #[test]
+    fn injected_errors_expire() {
+        let path = write_image("faults", 7);
+        let mut image = ImageDrive::open(&path, &layout()).unwrap();
+        image.inject_error(3, ErrorCode::UnknownReadError, 2);
+        for _ in 0..2 {
+            assert_eq!(
+                image.read_track(2).unwrap_err().code(),
+                Some(ErrorCode::UnknownReadError)
+            );
+        }
+        assert!(image.read_track(2).is_ok());
+
+        image.inject_error(0, ErrorCode::CannotReadAnyData, 1);
+        image.inject_error(0, ErrorCode::CannotReadAnyData, 0);
+        assert!(image.read_range(0..=0).is_ok());
+        fs::remove_file(path).unwrap();
+    }
+
+    #[test]
+    fn rejects_short_image() {
+        let path = write_image("short", 5);
+        assert!(ImageDrive::open(&path, &layout()).is_err());
+        fs::remove_file(path).unwrap();
+    }
+}
@@ -0,0 +1,582 @@
+//! Snapshot of the disc's table of contents.
+
+use std::ops::{Range, RangeInclusive};
+
+use crate::{
+    format::{Msf, PREGAP_SECTORS, SECTORS_PER_SECOND},
+    CdromDrive, Error, ErrorCode, CD_FRAMESAMPLES,
+};
+
+const TOC_FLAG_PREEMPHASIS: u8 = 0x01;
+const TOC_FLAG_COPY_PERMITTED: u8 = 0x02;
+const TOC_FLAG_DATA: u8 = 0x04;
+const TOC_FLAG_FOUR_CHANNEL: u8 = 0x08;
+
+/// One entry of the table of contents.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Track {
+    /// Track number, as accepted by the `CdromDrive::track_*` functions.
+    pub number: u32,
+    pub first_sector: u64,
+    pub last_sector: u64,
+    pub channels: u32,
+    pub audio: bool,
+    pub copy_permitted: bool,
+    pub preemphasis: bool,
+}
+
+impl Track {
+    /// The sectors of the track, first to last inclusive.
+    pub fn sector_range(&self) -> RangeInclusive<u64> {
+        self.first_sector..=self.last_sector
+    }
+}
+
+/// Runs of sectors and the track they belong to, see
+/// [`DiscLayout::track_map`].
+pub type TrackMap = Vec<(RangeInclusive<u64>, Option<u32>)>;
+
+/// The table of contents of an opened disc.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DiscLayout {
+    pub tracks: Vec<Track>,
+    /// First sector of the lead-out, one past the last sector of the last
+    /// track.
+    pub lead_out: u64,
+}
+
+/// An inconsistency found by [`DiscLayout::validate`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, displaydoc::Display)]
+pub enum TocAnomaly {
+    /// track {track} ends {sectors} sectors before the next track starts
+    Gap { track: u32, sectors: u64 },
+
+    /// track {track} overlaps the next track by {sectors} sectors
+    Overlap { track: u32, sectors: u64 },
+
+    /// track {track} ends after it starts
+    Backwards { track: u32 },
+
+    /// lead-out at sector {lead_out} is not after the last track ending at {last_sector}
+    LeadOutTooEarly { lead_out: u64, last_sector: u64 },
+}
+
+impl DiscLayout {
+    /// Checks that the tracks are contiguous and the lead-out follows the
+    /// last track, returning every anomaly found.
+    ///
+    /// Layouts from [`CdromDrive::read_layout`] are contiguous by
+    /// construction; this is mainly for layouts built or edited by hand.
+    pub fn validate(&self) -> Result<(), Vec<TocAnomaly>> {
+        let mut anomalies = Vec::new();
+        for track in &self.tracks {
+            if track.last_sector < track.first_sector {
+                anomalies.push(TocAnomaly::Backwards {
+                    track: track.number,
+                });
+            }
+        }
+        for pair in self.tracks.windows(2) {
+            let (track, next) = (&pair[0], &pair[1]);
+            let end = track.last_sector + 1;
+            if end < next.first_sector {
+                anomalies.push(TocAnomaly::Gap {
+                    track: track.number,
+                    sectors: next.first_sector - end,
+                });
+            } else if end > next.first_sector {
+                anomalies.push(TocAnomaly::Overlap {
+                    track: track.number,
+                    sectors: end - next.first_sector,
+                });
+            }
+        }
+        if let Some(last) = self.tracks.last() {
+            if self.lead_out <= last.last_sector {
+                anomalies.push(TocAnomaly::LeadOutTooEarly {
+                    lead_out: self.lead_out,
+                    last_sector: last.last_sector,
+                });
+            }
+        }
+
+        if anomalies.is_empty() {
+            Ok(())
+        } else {
+            Err(anomalies)
+        }
+    }
+
+    /// Looks up a track by number.
+    pub fn track(&self, number: u32) -> Option<&Track> {
+        let index = usize::try_from(number).ok()?.checked_sub(1)?;
+        self.tracks.get(index)
+    }
+
+    /// Splits `range` (absolute sectors, inclusive) into contiguous runs
+    /// belonging to the same track, in order, labeled with the track number
+    /// or `None` for sectors outside every track (such as past the
+    /// lead-out).
+    ///
+    /// This is the batched form of
+    /// [`CdromDrive::sector_get_track`], computed from the layout alone.
+    /// An empty range gives no runs.
+    pub fn track_map(&self, range: RangeInclusive<u64>) -> TrackMap {
+        let (mut cursor, end) = range.into_inner();
+        let mut runs = Vec::new();
+        while cursor <= end {
+            let containing = self
+                .tracks
+                .iter()
+                .find(|track| track.sector_range().contains(&cursor));
+            let (run_end, label) = match containing {
+                Some(track) => (track.last_sector, Some(track.number)),
+                None => {
+                    let next_start = self
+                        .tracks
+                        .iter()
+                        .map(|track| track.first_sector)
+                        .filter(|&first| first > cursor)
+                        .min();
+                    (next_start.map_or(u64::MAX, |first| first - 1), None)
+                }
+            };
+            let run_end = run_end.min(end);
+            runs.push((cursor..=run_end, label));
+            match run_end.checked_add(1) {
+                Some(next) => cursor = next,
+                None => break,
+            }
+        }
+        runs
+    }
+
+    /// Where a track's samples lie in a continuous rip of the whole disc
+    /// made without offset correction, as frame (stereo sample) indices
+    /// from the start of sector 0.
+    ///
+    /// A drive with a read offset of `read_offset` samples returns the
+    /// disc's sample `n` at position `n + read_offset`, so every boundary
+    /// shifts by that amount. The range is clipped to the part of the disc
+    /// the drive can return, from sector 0 up to the lead-out; it is
+    /// `None` if there is no such track.
+    pub fn track_sample_range(&self, track: u32, read_offset: i32) -> Option<Range<u64>> {
+        let track = self.track(track)?;
+        let frames = u64::from(CD_FRAMESAMPLES);
+        let end_of_disc = self.lead_out * frames;
+        let shift = |frame: u64| {
+            frame
+                .saturating_add_signed(read_offset.into())
+                .min(end_of_disc)
+        };
+        Some(shift(track.first_sector * frames)..shift((track.last_sector + 1) * frames))
+    }
+
+    /// Red Book MSF address of the first sector of track 1, which is
+    /// [`PREGAP_SECTORS`] after the end of the lead-in, as recorded in rip
+    /// logs. 00:02.00 for nearly every disc; `None` without tracks, or if
+    /// a malformed TOC puts it at 256 minutes or more.
+    pub fn first_track_msf(&self) -> Option<Msf> {
+        let first = self.tracks.first()?;
+        Msf::checked_from_lba(first.first_sector)
+    }
+
+    /// Red Book MSF address of the lead-out, counting the pregap, as
+    /// recorded in rip logs and cue sheets. `None` if a malformed TOC puts
+    /// it at 256 minutes or more.
+    pub fn lead_out_msf(&self) -> Option<Msf> {
+        Msf::checked_from_lba(self.lead_out)
+    }
+
+    /// The freedb (CDDB) disc id.
+    ///
+    /// The id is computed from Red Book addresses, so every sector position
+    /// includes the [`PREGAP_SECTORS`] before track 1.
+    pub fn freedb_id(&self) -> u32 {
+        let seconds = |sector: u64| (sector + PREGAP_SECTORS) / SECTORS_PER_SECOND;
+        let digit_sum = |mut n: u64| {
+            let mut sum = 0;
+            while n > 0 {
+                sum += n % 10;
+                n /= 10;
+            }
+            sum
+        };
+
+        let checksum: u64 = self
+            .tracks
+            .iter()
+            .map(|track| digit_sum(seconds(track.first_sector)))
+            .sum();
+        let first = self.tracks.first().map_or(0, |track| track.first_sector);
+        let length = seconds(self.lead_out) - seconds(first);
+        ((checksum % 255) << 24 | length << 8 | self.tracks.len() as u64) as u32
+    }
+}
+
+impl CdromDrive {
+    /// The sectors of an audio track, first to last inclusive.
+    ///
+    /// Returns [`ErrorCode::TrackNotAudio`] for data tracks, and
+    /// [`ErrorCode::IllegalToc`] for tracks a malformed TOC gives no sectors
+    /// (ending before they start).
+    pub fn track_sector_range(&self, track: u32) -> Result<RangeInclusive<u64>, Error> {
+        if !self.track_audiop(track)? {
+            return Err(Error::from_code(ErrorCode::TrackNotAudio));
+        }
+        let sectors = self.track_first_sector(track)?..=self.track_last_sector(track)?;
+        if sectors.is_empty() {
+            return Err(Error::from_code(ErrorCode::IllegalToc));
+        }
+        Ok(sectors)
+    }
+
+    /// Number of interleaved samples in an audio track, the length of what
+    /// [`CdromParanoia::read_track`](crate::CdromParanoia::read_track)
+    /// returns (see [`Samples`](crate::Samples), whose `len` this matches).
+    pub fn track_samples(&self, track: u32) -> Result<u64, Error> {
+        Ok(self.track_frames(track)? * 2)
+    }
+
+    /// Number of frames (samples per channel) in an audio track, which
+    /// with the 44100 Hz sample rate gives its exact duration. Matches
+    /// [`Samples::frames`](crate::Samples::frames).
+    pub fn track_frames(&self, track: u32) -> Result<u64, Error> {
+        let sectors = self.track_sector_range(track)?;
+        Ok((sectors.end() - sectors.start() + 1) * u64::from(CD_FRAMESAMPLES))
+    }
+
+    /// Number of interleaved samples on the disc, from the first audio
+    /// sector to the last, as [`track_samples`](Self::track_samples) counts
+    /// them. A data track lying between audio tracks is counted too.
+    pub fn total_samples(&self) -> Result<u64, Error> {
+        let sectors = self.disc_last_sector()? - self.disc_first_sector()? + 1;
+        Ok(sectors * u64::from(CD_FRAMESAMPLES) * 2)
+    }
+
+    /// Size in bytes of the samples counted by
+    /// [`total_samples`](Self::total_samples), 2352 per sector.
+    pub fn total_bytes(&self) -> Result<u64, Error> {
+        Ok(self.total_samples()? * 2)
+    }
+
+    /// Reads the table of contents of a drive that is only going to be
+    /// asked what is on the disc, doing less work than [`open`](Self::open)
+    /// followed by [`read_layout`](Self::read_layout).
+    ///
+    /// The library has no TOC-only entry point: the transport is only set
+    /// up by `cdda_open`. What this skips is `cdda_open`'s byte-order
+    /// detection, which reads and compares audio sectors and is the slow
+    /// part of opening. The drive is consumed because, without that
+    /// detection, audio read from it could come out byte-swapped. A drive
+    /// that is already open just has its layout read.
+    pub fn read_toc_only(self) -> Result<DiscLayout, Error> {
+        if !self.is_open() {
+            // cdda_open only probes the byte order while it is unknown (-1).
+            unsafe { (*self.as_raw()).bigendianp = cfg!(target_endian = "big").into() };
+            self.open()?;
+        }
+        self.read_layout()
+    }
+
+    /// See [`DiscLayout::track_map`]. Reads the layout once instead of
+    /// querying every sector.
+    pub fn track_map(&self, range: RangeInclusive<u64>) -> Result<TrackMap, Error> {
+        Ok(self.read_layout()?.track_map(range))
+    }
+
+    /// Reads the whole table of contents in one pass.
+    ///
+    /// This reads the TOC the library cached when the drive was opened,
+    /// instead of calling the `track_*` functions for every track, and
+    /// returns the same values they would.
+    pub fn read_layout(&self) -> Result<DiscLayout, Error> {
+        if !self.is_open() {
+            return Err(Error::from_code(ErrorCode::DeviceNotOpen));
+        }
+        let drive = unsafe { &*self.as_raw() };
+        let count: usize = drive
+            .tracks
+            .try_into()
+            .ok()
+            .filter(|&count| count < drive.disc_toc.len())
+            .ok_or(Error::from_code(ErrorCode::IllegalTrackCount))?;
+
+        let sector = |index: usize| -> Result<u64, Error> {
+            drive.disc_toc[index]
+                .dwStartSector
+                .try_into()
+                .map_err(|_| Error::from_code(ErrorCode::IllegalToc))
+        };
+
+        let mut tracks = Vec::with_capacity(count);
+        for index in 0..count {
+            let flags = drive.disc_toc[index].bFlags;
+            tracks.push(Track {
+                number: (index + 1) as u32,
+                first_sector: sector(index)?,
+                last_sector: sector(index + 1)?
+                    .checked_sub(1)
+                    .ok_or(Error::from_code(ErrorCode::IllegalToc))?,
+                channels: if flags & TOC_FLAG_FOUR_CHANNEL != 0 {
+                    4
+                } else {
+                    2
+                },
+                audio: flags & TOC_FLAG_DATA == 0,
+                copy_permitted: flags & TOC_FLAG_COPY_PERMITTED != 0,
+                preemphasis: flags & TOC_FLAG_PREEMPHASIS != 0,
+            });
+        }
+
+        Ok(DiscLayout {
+            tracks,
+            lead_out: sector(count)?,
+        })
+    }
+
+    /// Queries the tracks one at a time, from 1 up to
+    /// [`tracks`](Self::tracks), through the `track_*` functions. Data
+    /// tracks are included, with `audio` set to `false`.
+    ///
+    /// [`read_layout`](Self::read_layout) returns the same tracks but fails
+    /// as a whole; here a track that cannot be queried yields its error and
+    /// the following tracks are still tried. If the track count cannot be
+    /// read, its error is the only item.
+    pub fn tracks_iter(&self) -> impl Iterator<Item = Result<Track, Error>> + '_ {
+        let (count, error) = match self.tracks() {
+            Ok(count) => (count, None),
+            Err(error) => (0, Some(error)),
+        };
+        error
+            .map(Err)
+            .into_iter()
+            .chain((1..=count).map(|number| self.query_track(number)))
+    }
+
+    fn query_track(&self, number: u32) -> Result<Track, Error> {
+        Ok(Track {
+            number,
+            first_sector: self.track_first_sector(number)?,
+            last_sector: self.track_last_sector(number)?,
+            channels: self.track_channels(number)?,
+            audio: self.track_audiop(number)?,
+            copy_permitted: self.track_copyp(number)?,
+            preemphasis: self.track_preemp(number)?,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::testing::FakeDrive;
+
+    use super::*;
+
+    #[test]
+    fn matches_per_track_queries() {
+        let fake = FakeDrive::new(
+            &[(0x00, 0), (0x02, 15000), (0x09, 32000), (0x04, 50000)],
+            80000,
+        );
+        let drive = fake.drive();
+        let layout = drive.read_layout().unwrap();
+
+        assert_eq!(layout.tracks.len() as u32, drive.tracks().unwrap());
+        assert_eq!(layout.lead_out, 80000);
+        for track in &layout.tracks {
+            let n = track.number;
+            assert_eq!(track.first_sector, drive.track_first_sector(n).unwrap());
+            assert_eq!(track.last_sector, drive.track_last_sector(n).unwrap());
+            assert_eq!(track.channels, drive.track_channels(n).unwrap());
+            assert_eq!(track.audio, drive.track_audiop(n).unwrap());
+            assert_eq!(track.copy_permitted, drive.track_copyp(n).unwrap());
+            assert_eq!(track.preemphasis, drive.track_preemp(n).unwrap());
+        }
+        let queried: Vec<_> = drive.tracks_iter().collect::<Result<_, _>>().unwrap();
+        assert_eq!(queried, layout.tracks);
+        assert_eq!(layout.track(3).unwrap().channels, 4);
+        assert!(layout.track(0).is_none());
+        assert!(layout.track(5).is_none());
+    }
+
+    #[test]
+    fn freedb_id_counts_pregap() {
+        let layout = FakeDrive::new(&[(0x00, 0), (0x00, 15000)], 30000)
+            .drive()
+            .read_layout()
+            .unwrap();
+        // Track starts at 2s and 202s, lead-out at 402s.
+        assert_eq!(layout.freedb_id(), 0x0601_9002);
+    }
+
+    #[test]
+    fn redbook_lead_in_and_out() {
+        let layout = FakeDrive::new(&[(0x00, 0), (0x00, 15000)], 198592)
+            .drive()
+            .read_layout()
+            .unwrap();
+        assert_eq!(
+            layout.first_track_msf(),
+            Some(Msf::from_sector(PREGAP_SECTORS))
+        );
+        assert_eq!(layout.lead_out_msf().unwrap().to_string(), "44:09.67");
+        let empty = DiscLayout {
+            tracks: Vec::new(),
+            lead_out: 0,
+        };
+        assert_eq!(empty.first_track_msf(), None);
+        let malformed = DiscLayout {
+            tracks: Vec::new(),
+            lead_out: u64::MAX,
+        };
+        assert_eq!(malformed.lead_out_msf(), None);
+    }
+
+    #[test]
+    fn sector_ranges() {
+        let fake = FakeDrive::new(&[(0x00, 0), (0x04, 15000)], 30000);
+        let drive = fake.drive();
+        assert_eq!(drive.track_sector_range(1).unwrap(), 0..=14999);
+        assert_eq!(
+            drive.track_sector_range(2).unwrap_err().code(),
+            Some(ErrorCode::TrackNotAudio)
+        );
+        let layout = drive.read_layout().unwrap();
+        assert_eq!(layout.tracks[1].sector_range(), 15000..=29999);
+        assert_eq!(drive.track_frames(1).unwrap(), 15000 * 588);
+        assert_eq!(drive.track_samples(1).unwrap(), 15000 * 1176);
+        // Only the audio track counts toward the disc total.
+        assert_eq!(drive.total_samples().unwrap(), 15000 * 1176);
+        assert_eq!(drive.total_bytes().unwrap(), 15000 * 2352);
+    }
+
+    #[test]
+    fn maps_sectors_to_tracks() {
+        let fake = FakeDrive::new(&[(0x00, 0), (0x00, 100), (0x04, 250)], 300);
+        let drive = fake.drive();
+        assert_eq!(
+            drive.track_map(50..=320).unwrap(),
+            [
+                (50..=99, Some(1)),
+                (100..=249, Some(2)),
+                (250..=299, Some(3)),
+                (300..=320, None),
+            ]
+        );
+        assert_eq!(drive.track_map(120..=130).unwrap(), [(120..=130, Some(2))]);
+        assert_eq!(drive.track_map(RangeInclusive::new(5, 4)).unwrap(), []);
+        for (run, label) in drive.track_map(0..=299).unwrap() {
+            for sector in [*run.start(), *run.end()] {
+                assert_eq!(label, Some(drive.sector_get_track(sector).unwrap()));
+            }
+        }
+
+        // Gaps in a hand-built layout.
+        let layout = DiscLayout {
+            tracks: vec![audio_track(1, 10, 19), audio_track(2, 30, 39)],
+            lead_out: 40,
+        };
+        assert_eq!(
+            layout.track_map(0..=35),
+            [
+                (0..=9, None),
+                (10..=19, Some(1)),
+                (20..=29, None),
+                (30..=35, Some(2)),
+            ]
+        );
+    }
+
+    fn audio_track(number: u32, first_sector: u64, last_sector: u64) -> Track {
+        Track {
+            number,
+            first_sector,
+            last_sector,
+            channels: 2,
+            audio: true,
+            copy_permitted: false,
+            preemphasis: false,
+        }
+    }
+
+    #[test]
+    fn validates_read_layout() {
+        let fake = FakeDrive::new(&[(0x00, 0), (0x00, 15000)], 30000);
+        assert_eq!(fake.drive().read_layout().unwrap().validate(), Ok(()));
+    }
+
+    #[test]
+    fn flags_gaps() {
+        let layout = DiscLayout {
+            tracks: vec![audio_track(1, 0, 9999), audio_track(2, 10150, 20000)],
+            lead_out: 20001,
+        };
+        assert_eq!(
+            layout.validate(),
+            Err(vec![TocAnomaly::Gap {
+                track: 1,
+                sectors: 150
+            }])
+        );
+    }
+
+    #[test]
+    fn flags_overlaps_and_lead_out() {
+        let layout = DiscLayout {
+            tracks: vec![audio_track(1, 0, 10074), audio_track(2, 10000, 20000)],
+            lead_out: 15000,
+        };
+        assert_eq!(
+            layout.validate(),
+            Err(vec![
+                TocAnomaly::Overlap {
+                    track: 1,
+                    sectors: 75
+                },
+                TocAnomaly::LeadOutTooEarly {
+                    lead_out: 15000,
+                    last_sector: 20000
+                },
+            ])
+        );
+    }
+
+    #[test]
+    fn sample_ranges_shift_by_offset() {
+        let layout = FakeDrive::new(&[(0x00, 0), (0x00, 100)], 200)
+            .drive()
+            .read_layout()
+            .unwrap();
+        assert_eq!(layout.track_sample_range(2, 0), Some(58800..117600));
+        assert_eq!(layout.track_sample_range(2, 667), Some(58800 + 667..117600));
+        assert_eq!(layout.track_sample_range(1, 667), Some(667..58800 + 667));
+        assert_eq!(layout.track_sample_range(1, -667), Some(0..58800 - 667));
+        assert_eq!(layout.track_sample_range(3, 0), None);
+    }
+
+    #[test]
+    fn requires_open_drive() {
+        let mut fake = FakeDrive::new(&[(0x00, 0)], 1000);
+        fake.set_opened(false);
+        assert_eq!(
+            fake.drive().read_layout().unwrap_err().code(),
+            Some(ErrorCode::DeviceNotOpen)
+        );
+    }
+
+    #[test]
+    fn zero_length_track() {
+        let fake = FakeDrive::new(&[(0x00, 0), (0x00, 15000), (0x00, 15000)], 30000);
+        let drive = fake.drive();
+        assert_eq!(
+            drive.track_sector_range(2).unwrap_err().code(),
+            Some(ErrorCode::IllegalToc)
+        );
+        assert_eq!(
+            drive.track_frames(2).unwrap_err().code(),
+            Some(ErrorCode::IllegalToc)
+        );
+        assert_eq!(drive.track_sector_range(3).unwrap(), 15000..=29999);
+    }
+}
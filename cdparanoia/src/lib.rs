@@ -1,17 +1,92 @@
 use std::{
-    ffi::{c_char, c_int, c_long, CStr},
+    cell::Cell,
+    ffi::{c_char, c_int, c_long, CStr, CString},
     fmt,
-    io::SeekFrom,
-    ops::Deref,
+    io::{self, SeekFrom},
+    mem::ManuallyDrop,
+    ops::{Deref, DerefMut, Range, RangeInclusive},
+    path::Path,
     ptr::{null_mut, NonNull},
+    sync::OnceLock,
+    thread,
+    time::Duration,
 };
 
 use bitflags::bitflags;
 use libc::{c_void, SEEK_CUR, SEEK_END, SEEK_SET};
 
+use crate::telemetry::RecoveryWindow;
+
+mod accuraterip;
+#[cfg(unix)]
+mod builder;
+mod callback;
+mod cli;
+mod conceal;
+#[cfg(target_os = "linux")]
+mod control;
+mod crc;
+mod event;
+#[cfg(feature = "flac")]
+mod flac;
+pub mod format;
+mod image;
+mod layout;
+mod metering;
+#[cfg(unix)]
+mod probe;
+mod reader;
+mod recorder;
+#[cfg(feature = "resample")]
+mod resample;
+mod rip;
+mod riplog;
+mod samples;
+mod telemetry;
+#[cfg(test)]
+mod testing;
+mod text;
+
+pub use crate::accuraterip::{accuraterip_checksums, AccurateRipVersion, VerifyResult};
+#[cfg(unix)]
+pub use crate::builder::ParanoiaBuilder;
+pub use crate::cli::ParseModeError;
+pub use crate::conceal::Concealment;
+#[cfg(target_os = "linux")]
+pub use crate::control::{Atip, DiscIdentifiers, DriveWarning, Inquiry, MediaType};
+pub use crate::crc::Crc32;
+pub use crate::event::ParanoiaEvent;
+#[cfg(feature = "flac")]
+pub use crate::flac::{FlacWriter, WriteWrapper};
+pub use crate::image::ImageDrive;
+pub use crate::layout::{DiscLayout, TocAnomaly, Track, TrackMap};
+pub use crate::metering::{Meter, PeakRms};
+#[cfg(unix)]
+pub use crate::probe::ProbeReport;
+pub use crate::reader::ParanoiaReader;
+pub use crate::recorder::{EventRecorder, EventSummary, ProblemRegion, RecordedEvent};
+#[cfg(feature = "resample")]
+pub use crate::resample::Resampler;
+pub use crate::rip::{BufferedReader, Frames, ReadChunks, RipConfig, Ripper, TrackRip};
+pub use crate::riplog::{RipLog, TrackRipResult};
+#[cfg(feature = "bytemuck")]
+pub use crate::samples::sector_host_bytes;
+pub use crate::samples::{deinterleave, sector_bytes, Endianness, Samples};
+pub use crate::telemetry::{CorrectionMap, RipStats, SectorTelemetry};
+pub use crate::text::{decode_cd_string, CdTextEncoding};
 pub use cdparanoia_sys;
 pub use cdparanoia_sys::{CD_FRAMESAMPLES, CD_FRAMESIZE, CD_FRAMESIZE_RAW, CD_FRAMEWORDS};
 
+#[cfg(feature = "test-interface")]
+extern "C" {
+    /// Only built into libcdparanoia with `CDDA_TEST`, so not in its header.
+    fn cdda_identify_test(
+        filename: *const c_char,
+        messagedest: c_int,
+        messages: *mut *mut c_char,
+    ) -> *mut cdparanoia_sys::cdrom_drive;
+}
+
 pub fn cdda_version() -> &'static CStr {
     unsafe { CStr::from_ptr(cdparanoia_sys::cdda_version()) }
 }
@@ -20,14 +95,135 @@ pub fn paranoia_version() -> &'static CStr {
     unsafe { CStr::from_ptr(cdparanoia_sys::paranoia_version()) }
 }
 
+/// The `(major, minor)` release number of the paranoia library, parsed
+/// from [`paranoia_version`], e.g. `(10, 2)` for
+/// `"cdparanoia-III-10.2 libcdparanoia release 10.2 (September 11, 2008)"`.
+pub fn paranoia_release() -> Option<(u32, u32)> {
+    parse_release(paranoia_version().to_str().ok()?)
+}
+
+fn parse_release(version: &str) -> Option<(u32, u32)> {
+    let (_, rest) = version.split_once("release ")?;
+    let number = rest.split_whitespace().next()?;
+    let (major, minor) = number.split_once('.')?;
+    Some((major.parse().ok()?, minor.parse().ok()?))
+}
+
+/// Whether the library has `paranoia_read_limited`, which appeared in
+/// release 9.8. Unknown releases are assumed to have it.
+///
+/// [`CdromParanoia::read_limited`] falls back to `paranoia_read` without
+/// it. This only helps where the missing symbol is resolved lazily; a
+/// binary linked with immediate binding fails to load against such a
+/// library regardless.
+pub fn supports_read_limited() -> bool {
+    static SUPPORTED: OnceLock<bool> = OnceLock::new();
+    *SUPPORTED.get_or_init(|| paranoia_release().map_or(true, |release| release >= (9, 8)))
+}
+
 #[repr(u32)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum Verbosity {
     ForgetIt = cdparanoia_sys::CDDA_MESSAGE_FORGETIT,
     PrintIt = cdparanoia_sys::CDDA_MESSAGE_PRINTIT,
     LogIt = cdparanoia_sys::CDDA_MESSAGE_LOGIT,
 }
 
-#[derive(Debug)]
+impl Verbosity {
+    /// Converts a message destination stored by the library. Unknown values
+    /// are treated as [`Verbosity::ForgetIt`], which is also what the library
+    /// does with them.
+    fn from_raw(raw: c_int) -> Self {
+        match raw as u32 {
+            cdparanoia_sys::CDDA_MESSAGE_PRINTIT => Self::PrintIt,
+            cdparanoia_sys::CDDA_MESSAGE_LOGIT => Self::LogIt,
+            _ => Self::ForgetIt,
+        }
+    }
+}
+
+/// Accepts exactly the `CDDA_MESSAGE_*` values.
+impl TryFrom<u32> for Verbosity {
+    type Error = UnknownValueError;
+
+    fn try_from(raw: u32) -> Result<Self, Self::Error> {
+        match raw {
+            cdparanoia_sys::CDDA_MESSAGE_FORGETIT => Ok(Self::ForgetIt),
+            cdparanoia_sys::CDDA_MESSAGE_PRINTIT => Ok(Self::PrintIt),
+            cdparanoia_sys::CDDA_MESSAGE_LOGIT => Ok(Self::LogIt),
+            _ => Err(UnknownValueError { value: raw.into() }),
+        }
+    }
+}
+
+/// How the library accesses a drive, chosen when it is identified.
+#[repr(u32)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Interface {
+    /// SCSI commands through the old generic SCSI (`sg`) driver.
+    GenericScsi = cdparanoia_sys::GENERIC_SCSI,
+    /// The kernel CD-ROM driver's audio read ioctls. No SCSI commands can
+    /// be sent, and the driver may retry or correct reads on its own.
+    CookedIoctl = cdparanoia_sys::COOKED_IOCTL,
+    /// The library's simulated drive, for testing.
+    Test = cdparanoia_sys::TEST_INTERFACE,
+    /// SCSI commands through the `SG_IO` ioctl.
+    SgioScsi = cdparanoia_sys::SGIO_SCSI,
+    /// `SG_IO`, working around a kernel bug in its transport.
+    SgioScsiBuggy = cdparanoia_sys::SGIO_SCSI_BUGGY1,
+}
+
+impl Interface {
+    /// Whether the library sends SCSI commands to the drive itself.
+    pub fn is_scsi(self) -> bool {
+        matches!(
+            self,
+            Self::GenericScsi | Self::SgioScsi | Self::SgioScsiBuggy
+        )
+    }
+}
+
+/// Accepts exactly the interface values of `cdrom_drive`.
+impl TryFrom<u32> for Interface {
+    type Error = UnknownValueError;
+
+    fn try_from(raw: u32) -> Result<Self, Self::Error> {
+        match raw {
+            cdparanoia_sys::GENERIC_SCSI => Ok(Self::GenericScsi),
+            cdparanoia_sys::COOKED_IOCTL => Ok(Self::CookedIoctl),
+            cdparanoia_sys::TEST_INTERFACE => Ok(Self::Test),
+            cdparanoia_sys::SGIO_SCSI => Ok(Self::SgioScsi),
+            cdparanoia_sys::SGIO_SCSI_BUGGY1 => Ok(Self::SgioScsiBuggy),
+            _ => Err(UnknownValueError { value: raw.into() }),
+        }
+    }
+}
+
+/// An integer that does not correspond to any value of the enum it was
+/// converted to.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct UnknownValueError {
+    value: i64,
+}
+
+impl UnknownValueError {
+    pub fn value(&self) -> i64 {
+        self.value
+    }
+}
+
+impl fmt::Display for UnknownValueError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "unknown value: {}", self.value)
+    }
+}
+
+impl std::error::Error for UnknownValueError {}
+
+/// An error returned by libcdparanoia.
+///
+/// Two errors compare equal if they carry the same raw error number.
+#[derive(Debug, Clone, PartialEq, Eq)]
 pub struct Error {
     raw: c_int,
 }
@@ -49,6 +245,12 @@ impl Error {
         }
     }
 
+    pub fn from_code(code: ErrorCode) -> Self {
+        Self {
+            raw: -code.as_raw(),
+        }
+    }
+
     pub fn as_raw(&self) -> c_int {
         self.raw
     }
@@ -69,7 +271,115 @@ impl fmt::Display for Error {
 
 impl std::error::Error for Error {}
 
-#[derive(Debug, Clone, Copy, PartialEq, displaydoc::Display)]
+/// Wraps the error, with the [`io::ErrorKind`] closest to its code, or
+/// [`io::ErrorKind::Other`] if none fits. The message is preserved.
+impl From<Error> for io::Error {
+    fn from(error: Error) -> Self {
+        let kind = match error.code() {
+            Some(ErrorCode::PermissionDenied | ErrorCode::DataPermissionDenied) => {
+                io::ErrorKind::PermissionDenied
+            }
+            Some(ErrorCode::NoMediumPresent) => io::ErrorKind::NotFound,
+            Some(ErrorCode::InterfaceNotSupported | ErrorCode::OptionNotSupported) => {
+                io::ErrorKind::Unsupported
+            }
+            Some(ErrorCode::KernelMemoryError) => io::ErrorKind::OutOfMemory,
+            Some(ErrorCode::BufferTooSmall) => io::ErrorKind::InvalidInput,
+            _ => io::ErrorKind::Other,
+        };
+        io::Error::new(kind, error)
+    }
+}
+
+/// Failure to find or open a drive, together with the diagnostics the
+/// library logged while probing.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FindError {
+    error: Option<Error>,
+    messages: String,
+}
+
+impl FindError {
+    /// Fails a probe that identified no drive, taking the reason from the
+    /// messages if they show one, see [`error`](Self::error).
+    fn not_found(messages: String) -> Self {
+        Self {
+            error: classify_messages(&messages).map(Error::from_code),
+            messages,
+        }
+    }
+
+    /// Why no usable drive was found, if known: the error from opening
+    /// the drive, or the reason a drive could not be identified.
+    ///
+    /// When the library identifies no drive it gives no error code, so one
+    /// is taken from the system errors in [`messages`](Self::messages):
+    /// [`ErrorCode::PermissionDenied`] for a device that could not be
+    /// opened for lack of permission, [`ErrorCode::NoMediumPresent`] for one
+    /// without a disc. This needs [`Verbosity::LogIt`] and an English
+    /// locale; otherwise, or if the messages show no such reason, a drive
+    /// that could not be accessed is reported as not found.
+    pub fn error(&self) -> Option<&Error> {
+        self.error.as_ref()
+    }
+
+    /// Messages logged while probing and opening. Only collected when the
+    /// verbosity is [`Verbosity::LogIt`]; empty otherwise.
+    pub fn messages(&self) -> &str {
+        &self.messages
+    }
+
+    /// Whether a drive was found but could not be accessed for lack of
+    /// permission, typically fixed by adding the user to the group that
+    /// owns the device (often `cdrom`).
+    ///
+    /// Without an error code to go on, this looks for the system's
+    /// "Permission denied" message in [`messages`](Self::messages), so it
+    /// needs [`Verbosity::LogIt`] and an English locale.
+    pub fn permission_denied(&self) -> bool {
+        self.error
+            .as_ref()
+            .and_then(Error::code)
+            .is_some_and(|code| {
+                matches!(
+                    code,
+                    ErrorCode::PermissionDenied | ErrorCode::DataPermissionDenied
+                )
+            })
+            || self.messages.contains("Permission denied")
+    }
+}
+
+/// The error code matching the system errors the library logged while
+/// failing to identify a drive, if any.
+fn classify_messages(messages: &str) -> Option<ErrorCode> {
+    if messages.contains("Permission denied") || messages.contains("Operation not permitted") {
+        Some(ErrorCode::PermissionDenied)
+    } else if messages.contains("No medium found") {
+        Some(ErrorCode::NoMediumPresent)
+    } else {
+        None
+    }
+}
+
+impl fmt::Display for FindError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self.error {
+            Some(error) => write!(f, "Failed to open CDROM drive: {}", error),
+            None => write!(f, "No CDROM drive found"),
+        }
+    }
+}
+
+impl std::error::Error for FindError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        self.error
+            .as_ref()
+            .map(|error| error as &(dyn std::error::Error + 'static))
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, displaydoc::Display)]
 #[non_exhaustive]
 pub enum ErrorCode {
     /// 001: Unable to set CDROM to read audio mode
@@ -99,12 +409,21 @@ pub enum ErrorCode {
     /// 009: CDROM reporting illegal table of contents
     IllegalToc,
 
+    /// 010: Unaddressable sector
+    UnaddressableSector,
+
     /// 100: Interface not supported
     InterfaceNotSupported,
 
+    /// 101: Drive is neither a CDROM nor a WORM device
+    NotCdrom,
+
     /// 102: Permission denied on cdrom (ioctl) device
     PermissionDenied,
 
+    /// 103: Permission denied on cdrom (data) device
+    DataPermissionDenied,
+
     /// 300: Kernel memory error
     KernelMemoryError,
 
@@ -114,6 +433,9 @@ pub enum ErrorCode {
     /// 401: Invalid track number
     InvalidTrackNumber,
 
+    /// 402: Track not audio data
+    TrackNotAudio,
+
     /// 403: No audio tracks on disc
     NoAudioTracks,
 
@@ -122,6 +444,11 @@ pub enum ErrorCode {
 
     /// 405: Option not supported by drive
     OptionNotSupported,
+
+    // Not a library code: returned by this crate before calling the
+    // library, e.g. by `CdromDrive::read_sectors`.
+    /// 500: Buffer too small for the sectors requested
+    BufferTooSmall,
 }
 
 impl ErrorCode {
@@ -136,17 +463,96 @@ impl ErrorCode {
             7 => Some(Self::UnknownReadError),
             8 => Some(Self::NoCdromModel),
             9 => Some(Self::IllegalToc),
+            10 => Some(Self::UnaddressableSector),
             100 => Some(Self::InterfaceNotSupported),
+            101 => Some(Self::NotCdrom),
             102 => Some(Self::PermissionDenied),
+            103 => Some(Self::DataPermissionDenied),
             300 => Some(Self::KernelMemoryError),
             400 => Some(Self::DeviceNotOpen),
             401 => Some(Self::InvalidTrackNumber),
+            402 => Some(Self::TrackNotAudio),
             403 => Some(Self::NoAudioTracks),
             404 => Some(Self::NoMediumPresent),
             405 => Some(Self::OptionNotSupported),
+            500 => Some(Self::BufferTooSmall),
             _ => None,
         }
     }
+
+    /// The (positive) error number used by the library, e.g. 404 for
+    /// [`NoMediumPresent`](Self::NoMediumPresent).
+    pub fn as_raw(&self) -> c_int {
+        match self {
+            Self::NoReadMode => 1,
+            Self::NoTocLeadout => 2,
+            Self::IllegalTrackCount => 3,
+            Self::NoTocHeader => 4,
+            Self::NoTocEntry => 5,
+            Self::CannotReadAnyData => 6,
+            Self::UnknownReadError => 7,
+            Self::NoCdromModel => 8,
+            Self::IllegalToc => 9,
+            Self::UnaddressableSector => 10,
+            Self::InterfaceNotSupported => 100,
+            Self::NotCdrom => 101,
+            Self::PermissionDenied => 102,
+            Self::DataPermissionDenied => 103,
+            Self::KernelMemoryError => 300,
+            Self::DeviceNotOpen => 400,
+            Self::InvalidTrackNumber => 401,
+            Self::TrackNotAudio => 402,
+            Self::NoAudioTracks => 403,
+            Self::NoMediumPresent => 404,
+            Self::OptionNotSupported => 405,
+            Self::BufferTooSmall => 500,
+        }
+    }
+
+    /// Whether this error may go away by itself if the operation is retried
+    /// after a short delay, e.g. while a freshly inserted disc is still
+    /// spinning up and the drive cannot yet report a medium or TOC.
+    pub fn is_transient(&self) -> bool {
+        matches!(
+            self,
+            Self::NoMediumPresent
+                | Self::NoTocLeadout
+                | Self::NoTocHeader
+                | Self::NoTocEntry
+                | Self::NoReadMode
+        )
+    }
+
+    /// Whether this error leaves the drive or disc unusable as a whole, so
+    /// a rip should be aborted rather than the track retried or skipped.
+    ///
+    /// Errors about the medium, the TOC, permissions or the device are
+    /// fatal. Read errors and errors about a particular track, sector or
+    /// option are not. Some fatal errors are also
+    /// [transient](Self::is_transient) while a disc spins up, so opening
+    /// may still be retried.
+    pub fn is_fatal(&self) -> bool {
+        !matches!(
+            self,
+            Self::CannotReadAnyData
+                | Self::UnknownReadError
+                | Self::UnaddressableSector
+                | Self::InvalidTrackNumber
+                | Self::TrackNotAudio
+                | Self::OptionNotSupported
+                | Self::BufferTooSmall
+        )
+    }
+}
+
+/// Like [`ErrorCode::from_raw`], accepting both the positive error number
+/// and the negative return value.
+impl TryFrom<c_int> for ErrorCode {
+    type Error = UnknownValueError;
+
+    fn try_from(raw: c_int) -> Result<Self, Self::Error> {
+        Self::from_raw(raw).ok_or(UnknownValueError { value: raw.into() })
+    }
 }
 
 pub struct CddaString {
@@ -181,6 +587,7 @@ unsafe impl Send for CddaString {}
 unsafe impl Sync for CddaString {}
 
 bitflags! {
+    #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
     pub struct ParanoiaMode : u32 {
         const FRAGMENT = cdparanoia_sys::PARANOIA_MODE_FRAGMENT;
         const NEVERSKIP = cdparanoia_sys::PARANOIA_MODE_NEVERSKIP;
@@ -194,13 +601,105 @@ bitflags! {
     }
 }
 
+/// Defaults to [`ParanoiaMode::FULL`], the recommended mode and the one the
+/// library starts with.
+impl Default for ParanoiaMode {
+    fn default() -> Self {
+        Self::FULL
+    }
+}
+
+impl ParanoiaMode {
+    /// Flags that only act inside the verification engine.
+    const CORRECTION: Self = Self::FRAGMENT.union(Self::REPAIR).union(Self::SCRATCH);
+
+    /// The mode with the flags implied by the others added, as applied by
+    /// [`CdromParanoia::set_mode`].
+    ///
+    /// The library only runs its verification engine when [`VERIFY`] or
+    /// [`OVERLAP`] is set; otherwise reads go straight to the drive.
+    /// [`FRAGMENT`], [`REPAIR`] and [`SCRATCH`] work on the engine's
+    /// results, so if any of them is set without either engine flag,
+    /// [`VERIFY`] is added. Overlap-only modes, like the CLI's `-Y`, are
+    /// kept as they are.
+    ///
+    /// [`VERIFY`]: Self::VERIFY
+    /// [`OVERLAP`]: Self::OVERLAP
+    /// [`FRAGMENT`]: Self::FRAGMENT
+    /// [`REPAIR`]: Self::REPAIR
+    /// [`SCRATCH`]: Self::SCRATCH
+    pub fn normalized(self) -> Self {
+        if self.intersects(Self::CORRECTION) && !self.intersects(Self::VERIFY | Self::OVERLAP) {
+            self | Self::VERIFY
+        } else {
+            self
+        }
+    }
+}
+
+/// How often [`CdromParanoia::read_limited`] re-reads a sector that fails
+/// verification before giving up and skipping it.
+///
+/// The engine only checks the limit every 5 retries, so limits are rounded
+/// up to a multiple of 5, and are at least 5. Independently of the limit,
+/// it also skips once it has widened its overlap search to the maximum,
+/// which can come sooner. Skipping is disabled entirely by
+/// [`ParanoiaMode::NEVERSKIP`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Retries(c_int);
+
+/// Granularity at which the engine checks the retry limit.
+const RETRY_STEP: u32 = 5;
+
+impl Retries {
+    /// The fewest retries the engine honors, 5.
+    pub const fn fewest() -> Self {
+        Self(RETRY_STEP as c_int)
+    }
+
+    /// Re-read a failing sector up to `n` times, rounded up to a multiple
+    /// of 5 (and at least 5). Values too large for the library are
+    /// clamped to [`unlimited`](Self::unlimited).
+    pub fn limited(n: u32) -> Self {
+        let rounded = n.max(1).div_ceil(RETRY_STEP).checked_mul(RETRY_STEP);
+        rounded
+            .and_then(|n| n.try_into().ok())
+            .map_or(Self::unlimited(), Self)
+    }
+
+    /// No retry limit. The engine still skips once its overlap search is
+    /// exhausted, unless [`ParanoiaMode::NEVERSKIP`] is set.
+    pub const fn unlimited() -> Self {
+        // Not a multiple of the step, so the engine never reaches it.
+        Self(c_int::MAX)
+    }
+}
+
+/// 20 retries, what [`CdromParanoia::read`] uses.
+impl Default for Retries {
+    fn default() -> Self {
+        Self(20)
+    }
+}
+
+/// Receives library messages drained when a drive is dropped, see
+/// [`CdromDrive::set_message_sink`].
+type MessageSink = Box<dyn FnMut(&str) + Send>;
+
 pub struct CdromDrive {
     raw: NonNull<cdparanoia_sys::cdrom_drive>,
+    message_sink: Option<MessageSink>,
+    /// See [`requested_speed`](Self::requested_speed).
+    requested_speed: Cell<Option<i32>>,
 }
 
 impl CdromDrive {
     pub unsafe fn from_raw(raw: *mut cdparanoia_sys::cdrom_drive) -> Option<Self> {
-        NonNull::new(raw).map(|raw| Self { raw })
+        NonNull::new(raw).map(|raw| Self {
+            raw,
+            message_sink: None,
+            requested_speed: Cell::new(None),
+        })
     }
 
     pub fn as_raw(&self) -> *mut cdparanoia_sys::cdrom_drive {
@@ -208,168 +707,910 @@ impl CdromDrive {
     }
 
     pub fn into_raw(self) -> *mut cdparanoia_sys::cdrom_drive {
-        let raw = self.as_raw();
         // Avoid dropping self, otherwise cdda_close will be called in Drop
-        std::mem::forget(self);
-        raw
+        let mut this = ManuallyDrop::new(self);
+        this.message_sink = None;
+        this.as_raw()
     }
 
-    pub fn find_a_cdrom(verbosity: Verbosity) -> Option<Self> {
-        // TODO messages output
-        unsafe {
-            Self::from_raw(cdparanoia_sys::cdda_find_a_cdrom(
-                verbosity as c_int,
-                null_mut(),
-            ))
-        }
+    /// Gives up ownership of the drive without closing it, returning a
+    /// reference that lives for the rest of the program.
+    ///
+    /// Like [`into_raw`](Self::into_raw), `cdda_close` is never called by
+    /// Rust afterwards; whoever ends up owning the drive is responsible for
+    /// closing it exactly once.
+    pub fn leak(self) -> &'static mut cdparanoia_sys::cdrom_drive {
+        unsafe { &mut *self.into_raw() }
     }
 
-    pub fn identify(device: &CStr, verbosity: Verbosity) -> Option<Self> {
-        // TODO messages output
-        unsafe {
-            Self::from_raw(cdparanoia_sys::cdda_identify(
-                device.as_ptr(),
-                verbosity as c_int,
-                null_mut(),
-            ))
-        }
+    /// Wraps the drive so that it is not closed when the wrapper is dropped.
+    ///
+    /// See [`ManuallyClosed`].
+    pub fn manually_closed(self) -> ManuallyClosed {
+        ManuallyClosed::new(self)
     }
 
-    pub fn identify_scsi(
+    /// Probes the usual device nodes for a drive, without opening it.
+    ///
+    /// If none is found, the error carries the messages the library logged
+    /// while probing, which tell a machine without drives from one whose
+    /// drives could not be accessed; see [`FindError::error`] and
+    /// [`FindError::permission_denied`].
+    pub fn find_a_cdrom(verbosity: Verbosity) -> Result<Self, FindError> {
+        Self::identify_found(|messages| unsafe {
+            cdparanoia_sys::cdda_find_a_cdrom(verbosity as c_int, messages)
+        })
+    }
+
+    /// Finds a drive and opens it, so that a drive without a disc is
+    /// reported as an error rather than a handle that fails later.
+    ///
+    /// On failure, the returned error carries the messages logged by both
+    /// steps.
+    pub fn find_and_open(verbosity: Verbosity) -> Result<Self, FindError> {
+        Self::identify_and_open(|messages| unsafe {
+            cdparanoia_sys::cdda_find_a_cdrom(verbosity as c_int, messages)
+        })
+    }
+
+    /// Like [`identify_cooked`](Self::identify_cooked), but also opens the
+    /// drive, collecting the library's diagnostics if either step fails.
+    pub fn open_cooked(device: &CStr, verbosity: Verbosity) -> Result<Self, FindError> {
+        Self::identify_and_open(|messages| unsafe {
+            cdparanoia_sys::cdda_identify_cooked(device.as_ptr(), verbosity as c_int, messages)
+        })
+    }
+
+    /// Like [`identify_scsi`](Self::identify_scsi), but also opens the
+    /// drive, collecting the library's diagnostics if either step fails.
+    pub fn open_scsi(
         generic_device: &CStr,
         ioctl_device: &CStr,
         verbosity: Verbosity,
-    ) -> Option<Self> {
-        // TODO messages output
-        unsafe {
-            Self::from_raw(cdparanoia_sys::cdda_identify_scsi(
+    ) -> Result<Self, FindError> {
+        Self::identify_and_open(|messages| unsafe {
+            cdparanoia_sys::cdda_identify_scsi(
                 generic_device.as_ptr(),
                 ioctl_device.as_ptr(),
                 verbosity as c_int,
-                null_mut(),
-            ))
-        }
+                messages,
+            )
+        })
     }
 
-    pub fn identify_cooked(device: &CStr, verbosity: Verbosity) -> Option<Self> {
-        // TODO messages output
-        unsafe {
-            Self::from_raw(cdparanoia_sys::cdda_identify_cooked(
-                device.as_ptr(),
-                verbosity as c_int,
-                null_mut(),
-            ))
+    /// Calls one of the `cdda_find_a_cdrom`/`cdda_identify*` functions with
+    /// a message buffer, then opens the drive it returns.
+    fn identify_and_open(
+        identify: impl FnOnce(*mut *mut c_char) -> *mut cdparanoia_sys::cdrom_drive,
+    ) -> Result<Self, FindError> {
+        let (drive, mut messages) = Self::identify_logged(identify);
+        let Some(drive) = drive else {
+            return Err(FindError::not_found(messages));
+        };
+        if let Err(error) = drive.open() {
+            for log in [drive.messages(), drive.errors()].into_iter().flatten() {
+                messages.push_str(&log.to_string_lossy());
+            }
+            return Err(FindError {
+                error: Some(error),
+                messages,
+            });
         }
+        Ok(drive)
     }
 
-    pub fn set_verbosity(&self, error_verbosity: Verbosity, message_verbosity: Verbosity) {
-        unsafe {
-            cdparanoia_sys::cdda_verbose_set(
-                self.raw.as_ptr(),
-                error_verbosity as c_int,
-                message_verbosity as c_int,
-            );
-        }
+    /// Calls one of the `cdda_find_a_cdrom`/`cdda_identify*` functions with
+    /// a message buffer, failing with the messages if it finds no drive.
+    fn identify_found(
+        identify: impl FnOnce(*mut *mut c_char) -> *mut cdparanoia_sys::cdrom_drive,
+    ) -> Result<Self, FindError> {
+        let (drive, messages) = Self::identify_logged(identify);
+        drive.ok_or_else(|| FindError::not_found(messages))
     }
 
-    pub fn open(&self) -> Result<(), Error> {
-        Error::from_raw(unsafe { cdparanoia_sys::cdda_open(self.raw.as_ptr()) })
+    /// Calls one of the `cdda_find_a_cdrom`/`cdda_identify*` functions with
+    /// a message buffer, returning the drive and the messages logged.
+    fn identify_logged(
+        identify: impl FnOnce(*mut *mut c_char) -> *mut cdparanoia_sys::cdrom_drive,
+    ) -> (Option<Self>, String) {
+        let (drive, messages) = Self::identify_with(identify);
+        let messages = messages
+            .map(|m| m.to_string_lossy().into_owned())
+            .unwrap_or_default();
+        (drive, messages)
     }
 
-    pub fn set_speed(&self, speed: i32) -> Result<(), Error> {
-        Error::from_raw(unsafe {
-            cdparanoia_sys::cdda_speed_set(self.raw.as_ptr(), speed.try_into().unwrap())
+    /// Like [`identify_logged`](Self::identify_logged), keeping the
+    /// library's buffer.
+    fn identify_with(
+        identify: impl FnOnce(*mut *mut c_char) -> *mut cdparanoia_sys::cdrom_drive,
+    ) -> (Option<Self>, Option<CddaString>) {
+        let mut raw_messages = null_mut();
+        let drive = unsafe { Self::from_raw(identify(&mut raw_messages)) };
+        let messages = unsafe { CddaString::from_raw(raw_messages) };
+        (drive, messages)
+    }
+
+    /// Like [`find_a_cdrom`](Self::find_a_cdrom), also returning the
+    /// messages the library logged while probing, whether or not a drive
+    /// was found.
+    ///
+    /// Messages are only collected with [`Verbosity::LogIt`]; with
+    /// [`Verbosity::PrintIt`] they go to stderr instead and `None` is
+    /// returned.
+    pub fn find_a_cdrom_with_messages(verbosity: Verbosity) -> (Option<Self>, Option<CddaString>) {
+        Self::identify_with(|messages| unsafe {
+            cdparanoia_sys::cdda_find_a_cdrom(verbosity as c_int, messages)
         })
     }
 
-    pub fn disc_first_sector(&self) -> Result<u64, Error> {
-        let result = unsafe { cdparanoia_sys::cdda_disc_firstsector(self.raw.as_ptr()) };
-        Error::from_raw_long(result)?;
-        Ok(result.try_into().unwrap())
+    /// Identifies the drive at `device`, without opening it.
+    ///
+    /// If no drive is identified, the error says why where the library's
+    /// messages show it, which needs [`Verbosity::LogIt`] and an English
+    /// locale; see [`FindError::error`].
+    pub fn identify(device: &CStr, verbosity: Verbosity) -> Result<Self, FindError> {
+        Self::identify_found(|messages| unsafe {
+            cdparanoia_sys::cdda_identify(device.as_ptr(), verbosity as c_int, messages)
+        })
     }
 
-    pub fn track_first_sector(&self, track: u32) -> Result<u64, Error> {
-        let result = unsafe {
-            cdparanoia_sys::cdda_track_firstsector(self.raw.as_ptr(), track.try_into().unwrap())
-        };
-        Error::from_raw_long(result)?;
-        Ok(result.try_into().unwrap())
+    /// Like [`identify`](Self::identify), also returning the messages
+    /// logged, see
+    /// [`find_a_cdrom_with_messages`](Self::find_a_cdrom_with_messages).
+    pub fn identify_with_messages(
+        device: &CStr,
+        verbosity: Verbosity,
+    ) -> (Option<Self>, Option<CddaString>) {
+        Self::identify_with(|messages| unsafe {
+            cdparanoia_sys::cdda_identify(device.as_ptr(), verbosity as c_int, messages)
+        })
     }
 
-    pub fn track_last_sector(&self, track: u32) -> Result<u64, Error> {
-        let result = unsafe {
-            cdparanoia_sys::cdda_track_lastsector(self.raw.as_ptr(), track.try_into().unwrap())
-        };
-        Error::from_raw_long(result)?;
-        Ok(result.try_into().unwrap())
+    /// Identifies the drive at a SCSI generic device, using `ioctl_device`
+    /// for the ioctls it does not support, without opening it.
+    ///
+    /// If no drive is identified, the error says why where the library's
+    /// messages show it, which needs [`Verbosity::LogIt`] and an English
+    /// locale; see [`FindError::error`].
+    pub fn identify_scsi(
+        generic_device: &CStr,
+        ioctl_device: &CStr,
+        verbosity: Verbosity,
+    ) -> Result<Self, FindError> {
+        Self::identify_found(|messages| unsafe {
+            cdparanoia_sys::cdda_identify_scsi(
+                generic_device.as_ptr(),
+                ioctl_device.as_ptr(),
+                verbosity as c_int,
+                messages,
+            )
+        })
     }
 
-    pub fn sector_get_track(&self, sector: u64) -> Result<u32, Error> {
-        let result = unsafe {
-            cdparanoia_sys::cdda_sector_gettrack(self.raw.as_ptr(), sector.try_into().unwrap())
-        };
-        Error::from_raw(result)?;
-        Ok(result.try_into().unwrap())
+    /// Like [`identify_scsi`](Self::identify_scsi), also returning the
+    /// messages logged, see
+    /// [`find_a_cdrom_with_messages`](Self::find_a_cdrom_with_messages).
+    pub fn identify_scsi_with_messages(
+        generic_device: &CStr,
+        ioctl_device: &CStr,
+        verbosity: Verbosity,
+    ) -> (Option<Self>, Option<CddaString>) {
+        Self::identify_with(|messages| unsafe {
+            cdparanoia_sys::cdda_identify_scsi(
+                generic_device.as_ptr(),
+                ioctl_device.as_ptr(),
+                verbosity as c_int,
+                messages,
+            )
+        })
     }
 
-    pub fn tracks(&self) -> Result<u32, Error> {
-        let result = unsafe { cdparanoia_sys::cdda_tracks(self.raw.as_ptr()) };
-        Error::from_raw_long(result)?;
-        Ok(result.try_into().unwrap())
+    /// Identifies the drive at `device` through the kernel's cooked ioctl
+    /// interface, without opening it.
+    ///
+    /// If no drive is identified, the error says why where the library's
+    /// messages show it, which needs [`Verbosity::LogIt`] and an English
+    /// locale; see [`FindError::error`].
+    pub fn identify_cooked(device: &CStr, verbosity: Verbosity) -> Result<Self, FindError> {
+        Self::identify_found(|messages| unsafe {
+            cdparanoia_sys::cdda_identify_cooked(device.as_ptr(), verbosity as c_int, messages)
+        })
     }
 
-    pub fn track_channels(&self, track: u32) -> Result<u32, Error> {
-        let result = unsafe {
-            cdparanoia_sys::cdda_track_channels(self.raw.as_ptr(), track.try_into().unwrap())
-        };
-        Error::from_raw(result)?;
-        Ok(result.try_into().unwrap())
+    /// Like [`identify_cooked`](Self::identify_cooked), also returning the
+    /// messages logged, see
+    /// [`find_a_cdrom_with_messages`](Self::find_a_cdrom_with_messages).
+    pub fn identify_cooked_with_messages(
+        device: &CStr,
+        verbosity: Verbosity,
+    ) -> (Option<Self>, Option<CddaString>) {
+        Self::identify_with(|messages| unsafe {
+            cdparanoia_sys::cdda_identify_cooked(device.as_ptr(), verbosity as c_int, messages)
+        })
     }
 
-    pub fn track_audiop(&self, track: u32) -> Result<bool, Error> {
-        let result = unsafe {
-            cdparanoia_sys::cdda_track_audiop(self.raw.as_ptr(), track.try_into().unwrap())
-        };
-        Error::from_raw(result)?;
-        Ok(result != 0)
+    /// Identifies a simulated drive backed by `file`, a raw image of
+    /// 2352-byte sectors, through the library's test interface
+    /// ([`Interface::Test`]), which makes up a table of contents for it.
+    ///
+    /// This allows exercising reads, seeks and the paranoia engine without
+    /// hardware. It needs a libcdparanoia built with `CDDA_TEST` defined,
+    /// which distribution packages are not; otherwise linking fails with
+    /// `cdda_identify_test` missing. [`ImageDrive`] reads images without
+    /// that requirement, but bypasses the library.
+    #[cfg(feature = "test-interface")]
+    pub fn identify_test(file: &CStr, verbosity: Verbosity) -> Option<Self> {
+        Self::identify_with(|messages| unsafe {
+            cdda_identify_test(file.as_ptr(), verbosity as c_int, messages)
+        })
+        .0
     }
 
-    pub fn track_copyp(&self, track: u32) -> Result<bool, Error> {
-        let result = unsafe {
-            cdparanoia_sys::cdda_track_copyp(self.raw.as_ptr(), track.try_into().unwrap())
-        };
-        Error::from_raw(result)?;
-        Ok(result != 0)
+    /// Like [`identify`](Self::identify), taking an owned device name, e.g.
+    /// one kept from a previous [`identify_path`](Self::identify_path)
+    /// canonicalization.
+    ///
+    /// The name is passed to the library as is, without validation or
+    /// canonicalization. The library keeps its own copy, so `device` is
+    /// dropped on return.
+    pub fn identify_cstring(device: CString, verbosity: Verbosity) -> Result<Self, FindError> {
+        Self::identify(&device, verbosity)
     }
 
-    pub fn track_preemp(&self, track: u32) -> Result<bool, Error> {
-        let result = unsafe {
-            cdparanoia_sys::cdda_track_preemp(self.raw.as_ptr(), track.try_into().unwrap())
-        };
-        Error::from_raw(result)?;
-        Ok(result != 0)
+    /// Like [`identify`](Self::identify), taking a path.
+    ///
+    /// The path is canonicalized first (symlinks such as `/dev/cdrom`
+    /// resolved, trailing separators dropped, relative paths made absolute),
+    /// and the canonical path is what the library sees. This makes the
+    /// library choose the same transport whichever name the device is
+    /// given by. A path that cannot be resolved fails like a device
+    /// without a drive, with the reason in [`FindError::messages`].
+    #[cfg(unix)]
+    pub fn identify_path(
+        device: impl AsRef<Path>,
+        verbosity: Verbosity,
+    ) -> Result<Self, FindError> {
+        Self::identify(&canonical_device(device.as_ref())?, verbosity)
     }
 
-    pub fn messages(&self) -> Option<CddaString> {
-        unsafe { CddaString::from_raw(cdparanoia_sys::cdda_messages(self.raw.as_ptr())) }
+    /// Like [`identify_scsi`](Self::identify_scsi), taking paths that are
+    /// canonicalized as in [`identify_path`](Self::identify_path).
+    #[cfg(unix)]
+    pub fn identify_scsi_path(
+        generic_device: impl AsRef<Path>,
+        ioctl_device: impl AsRef<Path>,
+        verbosity: Verbosity,
+    ) -> Result<Self, FindError> {
+        Self::identify_scsi(
+            &canonical_device(generic_device.as_ref())?,
+            &canonical_device(ioctl_device.as_ref())?,
+            verbosity,
+        )
     }
 
-    pub fn errors(&self) -> Option<CddaString> {
-        unsafe { CddaString::from_raw(cdparanoia_sys::cdda_errors(self.raw.as_ptr())) }
+    /// Like [`identify_cooked`](Self::identify_cooked), taking a path that
+    /// is canonicalized as in [`identify_path`](Self::identify_path).
+    #[cfg(unix)]
+    pub fn identify_cooked_path(
+        device: impl AsRef<Path>,
+        verbosity: Verbosity,
+    ) -> Result<Self, FindError> {
+        Self::identify_cooked(&canonical_device(device.as_ref())?, verbosity)
     }
-}
 
-impl Drop for CdromDrive {
-    fn drop(&mut self) {
-        unsafe {
-            cdparanoia_sys::cdda_close(self.raw.as_ptr());
-        }
+    /// The current `(error, message)` verbosity.
+    pub fn verbosity(&self) -> (Verbosity, Verbosity) {
+        let raw = unsafe { &*self.raw.as_ptr() };
+        (
+            Verbosity::from_raw(raw.errordest),
+            Verbosity::from_raw(raw.messagedest),
+        )
     }
-}
 
-pub struct CdromParanoia {
-    drive: CdromDrive,
-    raw: NonNull<cdparanoia_sys::cdrom_paranoia>,
+    /// Sets the error and message verbosity, returning the previous
+    /// `(error, message)` values.
+    pub fn set_verbosity(
+        &self,
+        error_verbosity: Verbosity,
+        message_verbosity: Verbosity,
+    ) -> (Verbosity, Verbosity) {
+        let previous = self.verbosity();
+        unsafe {
+            cdparanoia_sys::cdda_verbose_set(
+                self.raw.as_ptr(),
+                error_verbosity as c_int,
+                message_verbosity as c_int,
+            );
+        }
+        previous
+    }
+
+    /// Sets the verbosity until the returned guard is dropped, then restores
+    /// the previous values.
+    pub fn with_verbosity(
+        &self,
+        error_verbosity: Verbosity,
+        message_verbosity: Verbosity,
+    ) -> VerbosityGuard<'_> {
+        let previous = self.set_verbosity(error_verbosity, message_verbosity);
+        VerbosityGuard {
+            drive: self,
+            previous,
+        }
+    }
+
+    /// Opens the drive and reads the disc's table of contents.
+    ///
+    /// Opening a drive that is already open does nothing and returns
+    /// `Ok(())`; the table of contents is not re-read.
+    ///
+    /// If opening fails the drive is left unopened, with
+    /// [`is_open`](Self::is_open) returning `false`, and `open` can simply
+    /// be called again, e.g. once a disc has been inserted. There is no
+    /// need to identify the drive again.
+    pub fn open(&self) -> Result<(), Error> {
+        if self.is_open() {
+            return Ok(());
+        }
+        Error::from_raw(unsafe { cdparanoia_sys::cdda_open(self.raw.as_ptr()) })
+    }
+
+    /// Like [`open`](Self::open), passing the messages and errors the
+    /// library logs to `on_message` and `on_error` instead of wherever the
+    /// verbosity sends them. Opening is where the library explains most of
+    /// what it finds out about the drive, e.g. why it cannot read audio.
+    ///
+    /// The verbosity is switched to [`Verbosity::LogIt`] for the call and
+    /// restored afterwards, overriding any
+    /// [`set_verbosity`](Self::set_verbosity) made before. The library
+    /// offers no live hook, so the callbacks run once opening has finished,
+    /// each at most once with everything logged. Anything already buffered
+    /// from earlier, such as identification messages, is included.
+    pub fn open_with_sink(
+        &self,
+        mut on_message: impl FnMut(&str),
+        mut on_error: impl FnMut(&str),
+    ) -> Result<(), Error> {
+        let (error_verbosity, message_verbosity) =
+            self.set_verbosity(Verbosity::LogIt, Verbosity::LogIt);
+        let result = self.open();
+        self.set_verbosity(error_verbosity, message_verbosity);
+        if let Some(messages) = self.messages() {
+            on_message(&messages.to_string_lossy());
+        }
+        if let Some(errors) = self.errors() {
+            on_error(&errors.to_string_lossy());
+        }
+        result
+    }
+
+    /// Whether the drive has been successfully opened.
+    pub fn is_open(&self) -> bool {
+        unsafe { (*self.raw.as_ptr()).opened != 0 }
+    }
+
+    /// The model string the drive reported when it was identified, e.g.
+    /// `"PLEXTOR DVDR   PX-716A 1.11"`.
+    pub fn model(&self) -> Option<&CStr> {
+        let model = unsafe { (*self.raw.as_ptr()).drive_model };
+        if model.is_null() {
+            None
+        } else {
+            Some(unsafe { CStr::from_ptr(model) })
+        }
+    }
+
+    /// How the library accesses the drive, or `None` if it set an interface
+    /// this crate does not know.
+    pub fn interface(&self) -> Option<Interface> {
+        let raw = unsafe { (*self.raw.as_ptr()).interface };
+        u32::try_from(raw).ok()?.try_into().ok()
+    }
+
+    /// Whether the drive returns audio samples big-endian, as detected when
+    /// it was opened. `false` before that.
+    pub fn is_big_endian(&self) -> bool {
+        unsafe { (*self.raw.as_ptr()).bigendianp == 1 }
+    }
+
+    /// The `(generic, ioctl)` device nodes in use, if the drive is accessed
+    /// through SCSI passthrough.
+    ///
+    /// These are the nodes [`identify_scsi`](Self::identify_scsi) takes, so
+    /// they can be used to reopen the same drive later. Returns `None` for
+    /// drives using the cooked ioctl or test interfaces.
+    pub fn scsi_devices(&self) -> Option<(CString, CString)> {
+        if !self.interface()?.is_scsi() {
+            return None;
+        }
+        let raw = unsafe { &*self.raw.as_ptr() };
+        if raw.cdda_device_name.is_null() || raw.ioctl_device_name.is_null() {
+            return None;
+        }
+        unsafe {
+            Some((
+                CStr::from_ptr(raw.cdda_device_name).to_owned(),
+                CStr::from_ptr(raw.ioctl_device_name).to_owned(),
+            ))
+        }
+    }
+
+    /// Opens the drive, retrying up to `attempts` times while the drive
+    /// reports a transient (not ready / spinning up) condition, sleeping for
+    /// `backoff` between attempts.
+    ///
+    /// Permanent errors (see [`ErrorCode::is_transient`]) are returned
+    /// immediately without retrying. An `attempts` of 0 is treated as 1.
+    pub fn open_retry(&self, attempts: u32, backoff: Duration) -> Result<(), Error> {
+        let mut remaining = attempts.max(1);
+        loop {
+            remaining -= 1;
+            match self.open() {
+                Err(err) if remaining > 0 && err.code().is_some_and(|c| c.is_transient()) => {
+                    thread::sleep(backoff);
+                }
+                result => return result,
+            }
+        }
+    }
+
+    pub fn set_speed(&self, speed: i32) -> Result<(), Error> {
+        Error::from_raw(unsafe {
+            cdparanoia_sys::cdda_speed_set(self.raw.as_ptr(), speed.try_into().unwrap())
+        })?;
+        self.requested_speed.set(Some(speed));
+        Ok(())
+    }
+
+    /// The speed last set successfully with [`set_speed`](Self::set_speed),
+    /// -1 for the drive's maximum, or `None` if it was never set. The drive
+    /// may read slower than asked; on Linux, `speed` reports what it
+    /// actually settled on.
+    pub fn requested_speed(&self) -> Option<i32> {
+        self.requested_speed.get()
+    }
+
+    /// Lets the drive read as fast as it can, undoing any earlier
+    /// [`set_speed`](Self::set_speed). This passes the library's "fastest"
+    /// value, -1.
+    pub fn set_speed_max(&self) -> Result<(), Error> {
+        self.set_speed(-1)
+    }
+
+    /// Whether the transport layer retries a failed sector read before
+    /// reporting the error.
+    pub fn error_retry(&self) -> bool {
+        unsafe { (*self.raw.as_ptr()).error_retry != 0 }
+    }
+
+    /// Enables or disables transport-level retries of failed reads.
+    ///
+    /// This happens below the paranoia layer: each read the paranoia engine
+    /// issues may be retried here, and the engine's own retries (see
+    /// [`CdromParanoia::read_limited`]) re-read and re-verify on top of that.
+    /// Enabled by default.
+    pub fn set_error_retry(&self, retry: bool) {
+        unsafe {
+            (*self.raw.as_ptr()).error_retry = retry.into();
+        }
+    }
+
+    /// Whether every transport error is reported, including those that a
+    /// retry recovered from.
+    pub fn report_all(&self) -> bool {
+        unsafe { (*self.raw.as_ptr()).report_all != 0 }
+    }
+
+    pub fn set_report_all(&self, report_all: bool) {
+        unsafe {
+            (*self.raw.as_ptr()).report_all = report_all.into();
+        }
+    }
+
+    pub fn disc_first_sector(&self) -> Result<u64, Error> {
+        let result = unsafe { cdparanoia_sys::cdda_disc_firstsector(self.raw.as_ptr()) };
+        Error::from_raw_long(result)?;
+        Ok(result.try_into().unwrap())
+    }
+
+    pub fn disc_last_sector(&self) -> Result<u64, Error> {
+        let result = unsafe { cdparanoia_sys::cdda_disc_lastsector(self.raw.as_ptr()) };
+        Error::from_raw_long(result)?;
+        Ok(result.try_into().unwrap())
+    }
+
+    /// Number of sectors from [`disc_first_sector`](Self::disc_first_sector)
+    /// to [`disc_last_sector`](Self::disc_last_sector), inclusive, e.g. as
+    /// the total for the progress of a whole-disc rip. Data tracks between
+    /// audio tracks are counted too.
+    pub fn disc_sectors(&self) -> Result<u64, Error> {
+        let first = self.disc_first_sector()?;
+        let last = self.disc_last_sector()?;
+        Ok((last + 1).saturating_sub(first))
+    }
+
+    /// Playing time of [`disc_sectors`](Self::disc_sectors).
+    pub fn disc_duration(&self) -> Result<Duration, Error> {
+        let sectors = self.disc_sectors()?;
+        let rate = format::SECTORS_PER_SECOND;
+        Ok(Duration::from_secs(sectors / rate)
+            + Duration::from_nanos(sectors % rate * 1_000_000_000 / rate))
+    }
+
+    pub fn track_first_sector(&self, track: u32) -> Result<u64, Error> {
+        let result = unsafe {
+            cdparanoia_sys::cdda_track_firstsector(self.raw.as_ptr(), track.try_into().unwrap())
+        };
+        Error::from_raw_long(result)?;
+        Ok(result.try_into().unwrap())
+    }
+
+    pub fn track_last_sector(&self, track: u32) -> Result<u64, Error> {
+        let result = unsafe {
+            cdparanoia_sys::cdda_track_lastsector(self.raw.as_ptr(), track.try_into().unwrap())
+        };
+        Error::from_raw_long(result)?;
+        Ok(result.try_into().unwrap())
+    }
+
+    pub fn sector_get_track(&self, sector: u64) -> Result<u32, Error> {
+        let result = unsafe {
+            cdparanoia_sys::cdda_sector_gettrack(self.raw.as_ptr(), sector.try_into().unwrap())
+        };
+        Error::from_raw(result)?;
+        Ok(result.try_into().unwrap())
+    }
+
+    pub fn tracks(&self) -> Result<u32, Error> {
+        let result = unsafe { cdparanoia_sys::cdda_tracks(self.raw.as_ptr()) };
+        Error::from_raw_long(result)?;
+        Ok(result.try_into().unwrap())
+    }
+
+    pub fn track_channels(&self, track: u32) -> Result<u32, Error> {
+        let result = unsafe {
+            cdparanoia_sys::cdda_track_channels(self.raw.as_ptr(), track.try_into().unwrap())
+        };
+        Error::from_raw(result)?;
+        Ok(result.try_into().unwrap())
+    }
+
+    /// Returns the channel count shared by every audio track on the disc, or
+    /// `None` if the audio tracks differ (or there are none).
+    ///
+    /// Data tracks are ignored. The tracks are queried once, stopping at the
+    /// first mismatch.
+    pub fn uniform_channels(&self) -> Result<Option<u32>, Error> {
+        let mut channels = None;
+        for track in 1..=self.tracks()? {
+            if !self.track_audiop(track)? {
+                continue;
+            }
+            let track_channels = self.track_channels(track)?;
+            match channels {
+                None => channels = Some(track_channels),
+                Some(n) if n != track_channels => return Ok(None),
+                Some(_) => {}
+            }
+        }
+        Ok(channels)
+    }
+
+    pub fn track_audiop(&self, track: u32) -> Result<bool, Error> {
+        let result = unsafe {
+            cdparanoia_sys::cdda_track_audiop(self.raw.as_ptr(), track.try_into().unwrap())
+        };
+        Error::from_raw(result)?;
+        Ok(result != 0)
+    }
+
+    pub fn track_copyp(&self, track: u32) -> Result<bool, Error> {
+        let result = unsafe {
+            cdparanoia_sys::cdda_track_copyp(self.raw.as_ptr(), track.try_into().unwrap())
+        };
+        Error::from_raw(result)?;
+        Ok(result != 0)
+    }
+
+    pub fn track_preemp(&self, track: u32) -> Result<bool, Error> {
+        let result = unsafe {
+            cdparanoia_sys::cdda_track_preemp(self.raw.as_ptr(), track.try_into().unwrap())
+        };
+        Error::from_raw(result)?;
+        Ok(result != 0)
+    }
+
+    pub fn messages(&self) -> Option<CddaString> {
+        unsafe { CddaString::from_raw(cdparanoia_sys::cdda_messages(self.raw.as_ptr())) }
+    }
+
+    pub fn errors(&self) -> Option<CddaString> {
+        unsafe { CddaString::from_raw(cdparanoia_sys::cdda_errors(self.raw.as_ptr())) }
+    }
+
+    /// Whether the library has buffered messages or errors that
+    /// [`messages`](Self::messages) or [`errors`](Self::errors) would
+    /// return. Cheap enough to poll after every read; nothing is consumed
+    /// or allocated.
+    pub fn has_pending_messages(&self) -> bool {
+        let raw = unsafe { &*self.raw.as_ptr() };
+        [raw.messagebuf, raw.errorbuf]
+            .into_iter()
+            .any(|buffer| !buffer.is_null() && unsafe { *buffer } != 0)
+    }
+
+    /// Discards the library's buffered messages and errors without reading
+    /// them, e.g. to start an operation with a clean slate. The message
+    /// sink, if any, does not see them.
+    pub fn clear_messages(&self) {
+        drop(self.messages());
+        drop(self.errors());
+    }
+
+    /// Registers a closure that receives the library's undrained messages
+    /// and errors when the drive is dropped, so the last diagnostics of a
+    /// failed rip are not lost. Replaces any previous sink.
+    ///
+    /// Only messages logged with [`Verbosity::LogIt`] are buffered by the
+    /// library. Drives released with [`into_raw`](Self::into_raw) or wrapped
+    /// in [`ManuallyClosed`] are not flushed.
+    pub fn set_message_sink(&mut self, sink: impl FnMut(&str) + Send + 'static) {
+        self.message_sink = Some(Box::new(sink));
+    }
+
+    /// Removes the sink registered with
+    /// [`set_message_sink`](Self::set_message_sink).
+    pub fn clear_message_sink(&mut self) {
+        self.message_sink = None;
+    }
+
+    /// Drains pending messages, then errors, into the message sink now. Does
+    /// nothing, and leaves the library's buffers alone, if no sink is set.
+    pub fn flush_messages(&mut self) {
+        if self.message_sink.is_none() {
+            return;
+        }
+        let logs = [self.messages(), self.errors()];
+        let sink = self.message_sink.as_mut().unwrap();
+        for log in logs.into_iter().flatten() {
+            let log = log.to_string_lossy();
+            if !log.is_empty() {
+                sink(&log);
+            }
+        }
+    }
+
+    /// Reads `count` sectors starting at `begin` straight from the drive
+    /// with `cdda_read` into `buf`, and returns the number of sectors read,
+    /// which may be fewer than asked for.
+    ///
+    /// This bypasses the paranoia engine, so it is fast but nothing is
+    /// verified: jitter, dropped bytes and scratches go unnoticed. A
+    /// `count` of 0 reads nothing and returns `Ok(0)`. Nothing is read
+    /// and an error returned if
+    ///
+    /// - `buf` is shorter than `count` sectors of [`CD_FRAMEWORDS`]
+    ///   samples: [`ErrorCode::BufferTooSmall`],
+    /// - any of the sectors is past the end of the last track:
+    ///   [`ErrorCode::UnaddressableSector`],
+    /// - or any of them belongs to a data track: [`ErrorCode::TrackNotAudio`].
+    pub fn read_sectors(&self, begin: u64, count: u32, buf: &mut [i16]) -> Result<u32, Error> {
+        self.read_sectors_via(begin, count, buf, |raw, buf, begin, count| unsafe {
+            cdparanoia_sys::cdda_read(raw, buf, begin, count)
+        })
+    }
+
+    /// Like [`read_sectors`](Self::read_sectors), also returning how long
+    /// the drive took, as measured by the library with `cdda_read_timed`,
+    /// e.g. to chart read throughput across the disc.
+    ///
+    /// The library measures in whole milliseconds. Returns
+    /// [`ErrorCode::OptionNotSupported`] before release 10.2, which lacks
+    /// the timed read.
+    pub fn read_sectors_timed(
+        &self,
+        begin: u64,
+        count: u32,
+        buf: &mut [i16],
+    ) -> Result<(u32, Duration), Error> {
+        if paranoia_release().is_some_and(|release| release < (10, 2)) {
+            return Err(Error::from_code(ErrorCode::OptionNotSupported));
+        }
+        let mut milliseconds: c_int = 0;
+        let read = self.read_sectors_via(begin, count, buf, |raw, buf, begin, count| unsafe {
+            cdparanoia_sys::cdda_read_timed(raw, buf, begin, count, &mut milliseconds)
+        })?;
+        let milliseconds = u64::try_from(milliseconds).unwrap_or(0);
+        Ok((read, Duration::from_millis(milliseconds)))
+    }
+
+    /// Checks the arguments of a raw read and makes it with `read`, which
+    /// is given the drive, buffer, first sector and sector count.
+    fn read_sectors_via(
+        &self,
+        begin: u64,
+        count: u32,
+        buf: &mut [i16],
+        read: impl FnOnce(*mut cdparanoia_sys::cdrom_drive, *mut c_void, c_long, c_long) -> c_long,
+    ) -> Result<u32, Error> {
+        if buf.len() / (CD_FRAMEWORDS as usize) < count as usize {
+            return Err(Error::from_code(ErrorCode::BufferTooSmall));
+        }
+        if count == 0 {
+            return Ok(0);
+        }
+        if !self.is_open() {
+            return Err(Error::from_code(ErrorCode::DeviceNotOpen));
+        }
+        let last = begin.saturating_add(u64::from(count) - 1);
+        if last > self.track_last_sector(self.tracks()?)? {
+            return Err(Error::from_code(ErrorCode::UnaddressableSector));
+        }
+        self.check_audio(begin, last)?;
+
+        let read = read(
+            self.raw.as_ptr(),
+            buf.as_mut_ptr() as *mut c_void,
+            begin.try_into().unwrap(),
+            count.into(),
+        );
+        Error::from_raw_long(read)?;
+        if read as u64 > u64::from(count) {
+            return Err(Error::from_code(ErrorCode::UnknownReadError));
+        }
+        Ok(read as u32)
+    }
+
+    /// See [`CdromParanoia::check_audio`].
+    pub(crate) fn check_audio(&self, first_sector: u64, last_sector: u64) -> Result<(), Error> {
+        // Sectors before track 1 (its pregap) report track 0.
+        let first_track = self.sector_get_track(first_sector)?.max(1);
+        let last_track = self.sector_get_track(last_sector)?.max(1);
+        for track in first_track..=last_track {
+            if !self.track_audiop(track)? {
+                return Err(Error::from_code(ErrorCode::TrackNotAudio));
+            }
+        }
+        Ok(())
+    }
+
+    /// Passes a message of this crate's own to the message sink, if any.
+    fn send_message(&mut self, message: &str) {
+        if let Some(sink) = &mut self.message_sink {
+            sink(message);
+        }
+    }
+
+    /// Closes the drive, reporting failure instead of ignoring it as
+    /// dropping does. Works the same whether or not the drive was ever
+    /// successfully opened.
+    ///
+    /// The message sink, if any, is flushed first.
+    pub fn close(self) -> Result<(), Error> {
+        let mut this = ManuallyDrop::new(self);
+        this.flush_messages();
+        this.message_sink = None;
+        Error::from_raw(unsafe { cdparanoia_sys::cdda_close(this.as_raw()) })
+    }
+}
+
+impl Drop for CdromDrive {
+    fn drop(&mut self) {
+        self.flush_messages();
+        unsafe {
+            cdparanoia_sys::cdda_close(self.raw.as_ptr());
+        }
+    }
+}
+
+// SAFETY: The drive owns its `cdrom_drive` exclusively, and libcdparanoia
+// keeps no per-thread state for it, so it may be used from whichever
+// thread owns it. It is not `Sync`: many `&self` methods (verbosity,
+// speed, message buffers) mutate the C struct without synchronization.
+unsafe impl Send for CdromDrive {}
+
+/// Restores a drive's previous verbosity when dropped.
+///
+/// Returned by [`CdromDrive::with_verbosity`].
+pub struct VerbosityGuard<'a> {
+    drive: &'a CdromDrive,
+    previous: (Verbosity, Verbosity),
+}
+
+impl Drop for VerbosityGuard<'_> {
+    fn drop(&mut self) {
+        let (error_verbosity, message_verbosity) = self.previous;
+        self.drive.set_verbosity(error_verbosity, message_verbosity);
+    }
+}
+
+/// A [`CdromDrive`] whose `Drop` does not call `cdda_close`.
+///
+/// This is for drives whose pointer is shared with C code that will close it
+/// itself. Unlike [`CdromDrive::into_raw`], the wrapper stays usable: it
+/// dereferences to the drive, so every drive method remains available.
+///
+/// The drive must be closed exactly once. Either let the C side close it and
+/// stop using the wrapper afterwards, or call [`close`](Self::close) or
+/// [`into_inner`](Self::into_inner) once the C side has let go of the
+/// pointer. Doing both closes the drive twice.
+pub struct ManuallyClosed {
+    drive: ManuallyDrop<CdromDrive>,
+}
+
+impl ManuallyClosed {
+    pub fn new(drive: CdromDrive) -> Self {
+        Self {
+            drive: ManuallyDrop::new(drive),
+        }
+    }
+
+    /// Returns the drive, which is again closed when dropped.
+    pub fn into_inner(self) -> CdromDrive {
+        ManuallyDrop::into_inner(self.drive)
+    }
+
+    /// Closes the drive now.
+    ///
+    /// # Safety
+    ///
+    /// No other owner may have closed the drive or close it later.
+    pub unsafe fn close(self) {
+        drop(self.into_inner());
+    }
+}
+
+impl Deref for ManuallyClosed {
+    type Target = CdromDrive;
+
+    fn deref(&self) -> &Self::Target {
+        &self.drive
+    }
+}
+
+impl DerefMut for ManuallyClosed {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.drive
+    }
+}
+
+/// Default number of reads [`CdromParanoia::is_recovering`] looks back over.
+const DEFAULT_RECOVERY_WINDOW: u32 = 8;
+
+/// Overlap `paranoia_init` starts the engine with, its maximum.
+const INITIAL_OVERLAP_SECTORS: i64 = 32;
+
+/// A paranoia reading session on a drive.
+///
+/// The drive must be opened with [`CdromDrive::open`] before being passed to
+/// [`init`](Self::init), since the engine is set up from the disc's TOC.
+/// Configure the session with [`set_mode`](Self::set_mode) and
+/// [`set_overlap`](Self::set_overlap) afterwards, then [`seek`](Self::seek)
+/// and read.
+///
+/// Read callbacks must not panic. A panic never unwinds into the library:
+/// in an `extern "C"` callback it aborts the process, and anywhere else on
+/// the callback path it is held until the library call has returned
+/// normally and then resumed.
+pub struct CdromParanoia {
+    drive: CdromDrive,
+    raw: NonNull<cdparanoia_sys::cdrom_paranoia>,
+    telemetry: SectorTelemetry,
+    recovery: RecoveryWindow,
+    /// Frames at the start of the next sector that a sub-sector seek asked
+    /// to discard. Cleared by every seek and read.
+    skip_frames: Cell<u16>,
+    /// Sector the next read returns, tracked across seeks and reads. `None`
+    /// until known, see [`tell`](Self::tell).
+    cursor: Cell<Option<u64>>,
+    /// Collects correction events while
+    /// [`read_track_mapped`](Self::read_track_mapped) runs.
+    corrections: Option<CorrectionMap>,
+    /// See [`set_event_recorder`](Self::set_event_recorder).
+    recorder: Option<EventRecorder>,
+    /// See [`overlap_samples`](Self::overlap_samples).
+    overlap: Cell<i64>,
 }
 
 impl CdromParanoia {
@@ -377,6 +1618,13 @@ impl CdromParanoia {
         Self {
             drive,
             raw: NonNull::new(raw).unwrap(),
+            telemetry: SectorTelemetry::default(),
+            recovery: RecoveryWindow::new(DEFAULT_RECOVERY_WINDOW),
+            skip_frames: Cell::new(0),
+            cursor: Cell::new(None),
+            corrections: None,
+            recorder: None,
+            overlap: Cell::new(INITIAL_OVERLAP_SECTORS * i64::from(CD_FRAMEWORDS)),
         }
     }
 
@@ -385,38 +1633,134 @@ impl CdromParanoia {
     }
 
     pub fn into_raw(self) -> (CdromDrive, *mut cdparanoia_sys::cdrom_paranoia) {
-        // Need to reconstruct the drive, it is not possible to move out of self.
-        let raw_drive = self.drive.as_raw();
-        let raw = self.as_raw();
-
-        // Avoid dropping self, otherwise cdda_close and paranoia_free will be
-        // called in Drop
-        std::mem::forget(self);
-
-        (unsafe { CdromDrive::from_raw(raw_drive).unwrap() }, raw)
+        // Avoid dropping self, otherwise paranoia_free will be called in Drop
+        let mut this = ManuallyDrop::new(self);
+        let raw = this.as_raw();
+        this.corrections = None;
+        this.recorder = None;
+        // The remaining fields own no resources, so only the drive needs to
+        // be moved out.
+        let drive = unsafe { std::ptr::read(&this.drive) };
+        (drive, raw)
     }
 
-    pub fn init(drive: CdromDrive) -> Self {
+    /// Starts a paranoia session on an opened drive.
+    ///
+    /// Returns [`ErrorCode::DeviceNotOpen`] if the drive has not been opened,
+    /// since the library would otherwise size its cache from a missing
+    /// table of contents.
+    pub fn init(drive: CdromDrive) -> Result<Self, Error> {
+        if !drive.is_open() {
+            return Err(Error::from_code(ErrorCode::DeviceNotOpen));
+        }
+        // The engine starts at the first audio sector.
+        let cursor = drive.disc_first_sector().ok();
         let raw = unsafe { cdparanoia_sys::paranoia_init(drive.as_raw()) };
-        unsafe { Self::from_raw(drive, raw) }
+        let paranoia = unsafe { Self::from_raw(drive, raw) };
+        paranoia.cursor.set(cursor);
+        Ok(paranoia)
     }
 
     pub fn drive(&self) -> &CdromDrive {
         &self.drive
     }
 
-    pub fn set_mode(&self, mode: ParanoiaMode) {
+    /// Ends the session, freeing the paranoia state, and returns the drive,
+    /// which stays open.
+    pub fn into_drive(self) -> CdromDrive {
+        let (drive, raw) = self.into_raw();
+        unsafe { cdparanoia_sys::paranoia_free(raw) };
+        drive
+    }
+
+    /// Sets the correction mode used by subsequent reads, and returns the
+    /// mode actually applied.
+    ///
+    /// Flags that would have no effect on their own bring in the flags they
+    /// depend on, see [`ParanoiaMode::normalized`].
+    ///
+    /// Returns [`ErrorCode::DeviceNotOpen`] without changing anything if the
+    /// drive has not been opened.
+    pub fn set_mode(&self, mode: ParanoiaMode) -> Result<ParanoiaMode, Error> {
+        self.ensure_open()?;
+        let mode = mode.normalized();
         unsafe {
             cdparanoia_sys::paranoia_modeset(self.raw.as_ptr(), mode.bits().try_into().unwrap());
         }
+        Ok(mode)
     }
 
-    pub fn set_overlap(&self, overlap: i64) {
+    /// Forces the overlap (in sectors) used when verifying reads, disabling
+    /// the engine's dynamic overlap adjustment.
+    ///
+    /// Returns [`ErrorCode::DeviceNotOpen`] without changing anything if the
+    /// drive has not been opened, and [`ErrorCode::OptionNotSupported`] if
+    /// the overlap in samples does not fit the library's `long`.
+    pub fn set_overlap(&self, overlap: i64) -> Result<(), Error> {
+        self.ensure_open()?;
+        let unsupported = || Error::from_code(ErrorCode::OptionNotSupported);
+        let samples = overlap
+            .checked_mul(i64::from(CD_FRAMEWORDS))
+            .ok_or_else(unsupported)?;
+        c_long::try_from(samples).map_err(|_| unsupported())?;
         unsafe {
-            cdparanoia_sys::paranoia_overlapset(self.raw.as_ptr(), overlap.try_into().unwrap());
+            cdparanoia_sys::paranoia_overlapset(self.raw.as_ptr(), overlap as c_long);
+        }
+        self.overlap.set(samples);
+        Ok(())
+    }
+
+    /// The overlap (in sectors, rounded down) the engine currently
+    /// verifies with, see [`overlap_samples`](Self::overlap_samples).
+    pub fn overlap(&self) -> i64 {
+        self.overlap.get() / i64::from(CD_FRAMEWORDS)
+    }
+
+    /// The overlap (in samples) the engine currently verifies with, as last
+    /// set by [`set_overlap`](Self::set_overlap) or reported by a
+    /// [`ParanoiaEvent::Overlap`] event during a read.
+    ///
+    /// The engine does not expose the value, so until one of those happens
+    /// this is the engine's initial overlap of 32 sectors.
+    pub fn overlap_samples(&self) -> i64 {
+        self.overlap.get()
+    }
+
+    fn ensure_open(&self) -> Result<(), Error> {
+        if self.drive.is_open() {
+            Ok(())
+        } else {
+            Err(Error::from_code(ErrorCode::DeviceNotOpen))
         }
     }
 
+    /// Returns the number of sectors paranoia assumes the drive caches.
+    pub fn cache_size(&self) -> u32 {
+        let result = unsafe { cdparanoia_sys::paranoia_cachemodel_size(self.raw.as_ptr(), -1) };
+        result.try_into().unwrap()
+    }
+
+    /// Sets the number of sectors paranoia assumes the drive caches.
+    ///
+    /// Paranoia seeks at least this far away before re-reading a sector, so
+    /// that a re-read comes from the disc and not the drive's cache. A
+    /// larger value defeats bigger drive caches (better correction on drives
+    /// that cache audio) at the cost of more seeking; a smaller value is
+    /// faster but may let cached, unverified data through. The library
+    /// default is 1200 sectors.
+    ///
+    /// The library accepts any non-negative `int`; larger values return
+    /// [`ErrorCode::OptionNotSupported`].
+    pub fn set_cache_size(&self, sectors: u32) -> Result<(), Error> {
+        let sectors: c_int = sectors
+            .try_into()
+            .map_err(|_| Error::from_code(ErrorCode::OptionNotSupported))?;
+        unsafe {
+            cdparanoia_sys::paranoia_cachemodel_size(self.raw.as_ptr(), sectors);
+        }
+        Ok(())
+    }
+
     pub fn seek(&self, pos: SeekFrom) -> Result<u64, Error> {
         let (mode, index): (c_int, c_long) = match pos {
             SeekFrom::Start(x) => (SEEK_SET, x.try_into().unwrap()),
@@ -424,39 +1768,609 @@ impl CdromParanoia {
             SeekFrom::Current(x) => (SEEK_CUR, x.try_into().unwrap()),
         };
 
+        self.skip_frames.set(0);
         let result = unsafe { cdparanoia_sys::paranoia_seek(self.raw.as_ptr(), index, mode) };
         Error::from_raw_long(result)?;
-        Ok(result.try_into().unwrap())
+        // The library returns the position before the seek.
+        let previous = result.try_into().unwrap();
+        let disc_last_sector = match pos {
+            SeekFrom::End(_) => self.drive.disc_last_sector().ok(),
+            _ => None,
+        };
+        self.cursor
+            .set(seek_target(pos, previous, disc_last_sector));
+        Ok(previous)
+    }
+
+    /// Seeks to the first sector of `track` and returns that sector.
+    pub fn seek_track(&self, track: u32) -> Result<u64, Error> {
+        let sector = self.drive.track_first_sector(track)?;
+        self.seek(SeekFrom::Start(sector))?;
+        Ok(sector)
+    }
+
+    /// Seeks to a Red Book address, as in a cue sheet (see
+    /// [`Msf::from_lba`](crate::format::Msf::from_lba)), and returns the
+    /// sector it names.
+    ///
+    /// Returns [`ErrorCode::UnaddressableSector`] for addresses inside the
+    /// pregap before track 1.
+    pub fn seek_msf(&self, msf: format::Msf) -> Result<u64, Error> {
+        let sector = msf
+            .to_lba()
+            .ok_or(Error::from_code(ErrorCode::UnaddressableSector))?;
+        self.seek(SeekFrom::Start(sector))?;
+        Ok(sector)
+    }
+
+    /// The sector the next read returns.
+    ///
+    /// Follows [`seek`](Self::seek) and every read, each of which advances
+    /// by one sector. Returns [`ErrorCode::InvalidTrackNumber`] if the
+    /// position is outside the disc, from its first audio sector to one
+    /// past its last (where the next read fails).
+    ///
+    /// For a session created with [`from_raw`](Self::from_raw), the position
+    /// is first learned by seeking to it, which discards verified data like
+    /// [`flush`](Self::flush).
+    pub fn tell(&self) -> Result<u64, Error> {
+        self.ensure_open()?;
+        let cursor = match self.cursor.get() {
+            Some(cursor) => cursor,
+            None => self.seek(SeekFrom::Current(0))?,
+        };
+        let disc = self.drive.disc_first_sector()?..=self.drive.disc_last_sector()? + 1;
+        if !disc.contains(&cursor) {
+            return Err(Error::from_code(ErrorCode::InvalidTrackNumber));
+        }
+        Ok(cursor)
+    }
+
+    /// Discards the verified data the engine has accumulated, so the next
+    /// read is checked against fresh reads only. The position is kept.
+    ///
+    /// Every seek already does this, so reads that start with a seek, like
+    /// [`read_range`](Self::read_range) and [`read_track`](Self::read_track),
+    /// never need it. Call it at track boundaries when reading on without
+    /// seeking, e.g. with [`read`](Self::read) or a
+    /// [`ParanoiaReader`] spanning several tracks. Sectors the engine read
+    /// ahead stay in its cache, but are verified again before use.
+    pub fn flush(&self) -> Result<(), Error> {
+        self.seek(SeekFrom::Current(0)).map(drop)
     }
 
     /// Reads the next sector of audio data and returns a full sector of
     /// verified samples (1176 samples, 2352 bytes).
+    ///
+    /// The returned buffer belongs to the paranoia engine and is only valid
+    /// until the next read or seek; the borrow of `self` enforces this for
+    /// safe code, but pointers derived from it must not outlive the borrow.
+    /// Use [`read_into`](Self::read_into) to keep a copy.
+    ///
+    /// `retries` bounds how often a sector that fails verification is
+    /// re-read before it is skipped; see [`Retries`]. Libraries without
+    /// `paranoia_read_limited` (see [`supports_read_limited`]) ignore it and
+    /// always use their fixed limit of 20 retries.
+    ///
+    /// Drops a pending sub-sector skip, like [`read`](Self::read).
     pub fn read_limited(
         &mut self,
         callback: extern "C" fn(c_long, c_int),
-        max_retries: u32,
+        retries: Retries,
     ) -> &[i16; CD_FRAMEWORDS as usize] {
-        let ptr = unsafe {
-            cdparanoia_sys::paranoia_read_limited(
-                self.raw.as_ptr(),
-                Some(callback),
-                max_retries.try_into().unwrap(),
-            )
-        };
+        self.discard_skip();
+        let ptr = self.read_raw(Some(callback), Some(retries));
         unsafe { &*(ptr as *const [i16; CD_FRAMEWORDS as usize]) }
     }
 
     /// Reads the next sector of audio data and returns a full sector of
     /// verified samples (1176 samples, 2352 bytes).
     ///
-    /// Identical to `read_limited` with `max_retries = 20`.
+    /// Identical to `read_limited` with [`Retries::default()`]. The same buffer
+    /// invalidation rules apply.
+    ///
+    /// The samples are in the host's byte order, whatever the drive's: the
+    /// library swaps them if the drive's order, detected when it was opened
+    /// (see [`CdromDrive::is_big_endian`]), differs. Use
+    /// [`read_in_order`](Self::read_in_order) for a fixed order in memory.
+    ///
+    /// A whole sector cannot start in the middle, so after a
+    /// [`seek_frame`](Self::seek_frame) into a sector this drops the
+    /// pending skip and returns the sector from its first frame. Use
+    /// [`read_samples`](Self::read_samples) or
+    /// [`read_into`](Self::read_into) to start exactly at the frame. This
+    /// applies to every read returning a whole sector, e.g.
+    /// [`read_with`](Self::read_with) and
+    /// [`read_bytes`](Self::read_bytes).
     pub fn read(
         &mut self,
         callback: extern "C" fn(c_long, c_int),
     ) -> &[i16; CD_FRAMEWORDS as usize] {
-        let ptr = unsafe { cdparanoia_sys::paranoia_read(self.raw.as_ptr(), Some(callback)) };
+        self.discard_skip();
+        let ptr = self.read_raw(Some(callback), None);
+        unsafe { &*(ptr as *const [i16; CD_FRAMEWORDS as usize]) }
+    }
+
+    /// Like [`read`](Self::read), but returns a copy of the sector whose
+    /// samples are laid out in memory in the given byte order, see
+    /// [`Endianness::arrange`]. Only the copy is made when that is the
+    /// host's order.
+    pub fn read_in_order(
+        &mut self,
+        callback: extern "C" fn(c_long, c_int),
+        endianness: Endianness,
+    ) -> [i16; CD_FRAMEWORDS as usize] {
+        self.read(callback).map(|sample| endianness.arrange(sample))
+    }
+
+    /// Like [`read`](Self::read), but passes the engine's events to a
+    /// closure, which unlike a bare function can capture state, e.g. to
+    /// count retries per sector or drive a progress bar. The events come
+    /// decoded, with their positions.
+    ///
+    /// The closure only receives events of this read. If it panics, it
+    /// receives no further events, the read finishes, and the panic is
+    /// resumed before this returns.
+    pub fn read_with(
+        &mut self,
+        mut callback: impl FnMut(c_long, ParanoiaEvent),
+    ) -> &[i16; CD_FRAMEWORDS as usize] {
+        self.discard_skip();
+        let ptr = self.read_raw_with(
+            &mut |position, event| callback(position, ParanoiaEvent::from_raw(event)),
+            None,
+        );
         unsafe { &*(ptr as *const [i16; CD_FRAMEWORDS as usize]) }
     }
+
+    /// Like [`read_limited`](Self::read_limited), but passes the engine's
+    /// events to a closure, see [`read_with`](Self::read_with).
+    pub fn read_limited_with(
+        &mut self,
+        mut callback: impl FnMut(c_long, ParanoiaEvent),
+        retries: Retries,
+    ) -> &[i16; CD_FRAMEWORDS as usize] {
+        self.discard_skip();
+        let ptr = self.read_raw_with(
+            &mut |position, event| callback(position, ParanoiaEvent::from_raw(event)),
+            Some(retries),
+        );
+        unsafe { &*(ptr as *const [i16; CD_FRAMEWORDS as usize]) }
+    }
+
+    /// Like [`read`](Self::read), for when the engine's events are of no
+    /// interest. The session's own bookkeeping, such as
+    /// [`last_sector_telemetry`](Self::last_sector_telemetry), still sees
+    /// them.
+    pub fn read_silent(&mut self) -> &[i16; CD_FRAMEWORDS as usize] {
+        self.discard_skip();
+        let ptr = self.read_raw(None, None);
+        unsafe { &*(ptr as *const [i16; CD_FRAMEWORDS as usize]) }
+    }
+
+    /// Like [`read_limited`](Self::read_limited), without a callback, see
+    /// [`read_silent`](Self::read_silent).
+    pub fn read_limited_silent(&mut self, retries: Retries) -> &[i16; CD_FRAMEWORDS as usize] {
+        self.discard_skip();
+        let ptr = self.read_raw(None, Some(retries));
+        unsafe { &*(ptr as *const [i16; CD_FRAMEWORDS as usize]) }
+    }
+
+    /// Like [`read`](Self::read), but views the sector as bytes, without
+    /// copying. The bytes are in the host's byte order, see
+    /// [`read`](Self::read); only on little-endian hosts are they what WAV
+    /// wants. Use [`read_bytes_in`](Self::read_bytes_in) for a fixed order.
+    pub fn read_bytes(
+        &mut self,
+        callback: extern "C" fn(c_long, c_int),
+    ) -> &[u8; CD_FRAMESIZE_RAW as usize] {
+        let samples = self.read(callback);
+        // Same size, and u8 has no alignment requirement.
+        unsafe { &*(samples as *const [i16; CD_FRAMEWORDS as usize]).cast() }
+    }
+
+    /// Like [`read_bytes`](Self::read_bytes), but returns a copy in the given
+    /// byte order, e.g. [`Endianness::Little`] for WAV on any host.
+    pub fn read_bytes_in(
+        &mut self,
+        callback: extern "C" fn(c_long, c_int),
+        endianness: Endianness,
+    ) -> [u8; CD_FRAMESIZE_RAW as usize] {
+        sector_bytes(self.read(callback), endianness)
+    }
+
+    /// Reads the next sector and returns the engine's buffer as a raw
+    /// pointer, without wrapping or copying it.
+    ///
+    /// The pointer is non-null and points to [`CD_FRAMEWORDS`] interleaved
+    /// samples. It stays valid only until the next read, seek, or drop of
+    /// this session, and the engine rewrites the same memory then, so it
+    /// must not be read after any of those. Since the borrow of `self` ends
+    /// on return, the compiler does not enforce this; prefer
+    /// [`read`](Self::read) wherever it is fast enough.
+    ///
+    /// Returns [`ErrorCode::UnaddressableSector`] without reading if a
+    /// sub-sector seek is pending, see [`seek_frame`](Self::seek_frame).
+    pub fn read_raw_ptr(
+        &mut self,
+        callback: extern "C" fn(c_long, c_int),
+    ) -> Result<*const i16, Error> {
+        Ok(self.read_checked(Some(callback))?.as_ptr())
+    }
+
+    /// Reads the next sector like [`read`](Self::read), minus any leading
+    /// frames a sub-sector seek asked to skip.
+    ///
+    /// Only the first read after such a seek is shortened.
+    pub fn read_samples(
+        &mut self,
+        callback: extern "C" fn(c_long, c_int),
+    ) -> Result<&[i16], Error> {
+        let skip = self.skip_frames.replace(0);
+        let sector = self.read_checked(Some(callback))?;
+        Ok(trim_leading_frames(sector, skip))
+    }
+
+    /// Frames [`read_samples`](Self::read_samples) and
+    /// [`read_into`](Self::read_into) will drop from the next sector.
+    pub fn skip_frames(&self) -> u16 {
+        self.skip_frames.get()
+    }
+
+    /// Seeks to an absolute frame (stereo sample) position, which need not
+    /// be at a sector boundary, and returns the sector seeked to.
+    ///
+    /// The next [`read_samples`](Self::read_samples) or
+    /// [`read_into`](Self::read_into) starts exactly at `frame`. A
+    /// whole-sector read such as [`read`](Self::read) instead drops the
+    /// skip and reads from the start of the sector.
+    pub fn seek_frame(&mut self, frame: u64) -> Result<u64, Error> {
+        let frames_per_sector = u64::from(CD_FRAMESAMPLES);
+        let sector = frame / frames_per_sector;
+        self.seek(SeekFrom::Start(sector))?;
+        self.skip_frames
+            .set((frame % frames_per_sector).try_into().unwrap());
+        Ok(sector)
+    }
+
+    /// Reads the interleaved sample at `index`, counted from the first
+    /// sample of sector 0, e.g. for a scrubbing display.
+    ///
+    /// Seeks to and reads the whole enclosing sector, so this is for random
+    /// access only; reading consecutive samples this way rereads every
+    /// sector [`CD_FRAMEWORDS`] times. Use
+    /// [`read_range`](Self::read_range) for bulk reads. Returns
+    /// [`ErrorCode::UnaddressableSector`] for `u64::MAX`, which no sector
+    /// holds.
+    pub fn read_sample(&mut self, index: u64) -> Result<i16, Error> {
+        let end = index
+            .checked_add(1)
+            .ok_or(Error::from_code(ErrorCode::UnaddressableSector))?;
+        Ok(self.read_sample_window(index..end)?[0])
+    }
+
+    /// Like [`read_sample`](Self::read_sample), for the interleaved samples
+    /// at the indices in `range`. Only the sectors overlapping the window
+    /// are read.
+    ///
+    /// Returns [`ErrorCode::TrackNotAudio`] if the window reaches into a
+    /// data track.
+    pub fn read_sample_window(&mut self, range: Range<u64>) -> Result<Vec<i16>, Error> {
+        if range.is_empty() {
+            return Ok(Vec::new());
+        }
+        let words = u64::from(CD_FRAMEWORDS);
+        let first_sector = range.start / words;
+        let last_sector = (range.end - 1) / words;
+        let mut samples = self.read_range_with(first_sector..=last_sector, None)?;
+        let skip = (range.start - first_sector * words) as usize;
+        samples.truncate(skip + (range.end - range.start) as usize);
+        samples.drain(..skip);
+        Ok(samples)
+    }
+
+    /// Returns what the engine reported while producing the most recently
+    /// read sector.
+    pub fn last_sector_telemetry(&self) -> SectorTelemetry {
+        self.telemetry
+    }
+
+    /// Whether the engine is struggling with the disc: any of the last few
+    /// reads (8 by default) was troubled, as reported by
+    /// [`SectorTelemetry::is_troubled`].
+    ///
+    /// This is meant for live feedback such as a "recovering..." status.
+    pub fn is_recovering(&self) -> bool {
+        self.recovery.is_recovering()
+    }
+
+    /// Sets how many reads [`is_recovering`](Self::is_recovering) looks back
+    /// over.
+    pub fn set_recovery_window(&mut self, reads: u32) {
+        self.recovery.set_window(reads);
+    }
+
+    /// Forgets past trouble, e.g. after seeking to a new track.
+    pub fn reset_recovery(&mut self) {
+        self.recovery.reset();
+    }
+
+    /// Reads the next sector of audio data and copies the verified samples
+    /// to the start of `buf`, which stays valid independently of further
+    /// reads, returning the number of samples copied.
+    ///
+    /// That is a whole sector, except for the first read after a sub-sector
+    /// [`seek_frame`](Self::seek_frame), which is shortened as in
+    /// [`read_samples`](Self::read_samples).
+    pub fn read_into(
+        &mut self,
+        callback: extern "C" fn(c_long, c_int),
+        buf: &mut [i16; CD_FRAMEWORDS as usize],
+    ) -> Result<usize, Error> {
+        let samples = self.read_samples(callback)?;
+        buf[..samples.len()].copy_from_slice(samples);
+        Ok(samples.len())
+    }
+
+    /// Reads an audio track from start to end and returns the CRC-32 of its
+    /// samples (as little-endian bytes), without keeping the data around.
+    ///
+    /// Two rips of the same track are identical if their CRCs match.
+    pub fn track_crc(&mut self, track: u32) -> Result<u32, Error> {
+        if !self.drive.track_audiop(track)? {
+            return Err(Error::from_code(ErrorCode::TrackNotAudio));
+        }
+        let first_sector = self.drive.track_first_sector(track)?;
+        let last_sector = self.drive.track_last_sector(track)?;
+        self.seek(SeekFrom::Start(first_sector))?;
+
+        let mut crc = Crc32::new();
+        for _ in first_sector..=last_sector {
+            crc.update_samples(self.read_checked(None)?);
+        }
+        Ok(crc.finish())
+    }
+
+    /// Reads a whole audio track into one buffer of interleaved stereo
+    /// samples.
+    ///
+    /// Returns [`ErrorCode::IllegalToc`] if the TOC gives the track no
+    /// sectors.
+    pub fn read_track(
+        &mut self,
+        track: u32,
+        callback: extern "C" fn(c_long, c_int),
+    ) -> Result<Samples, Error> {
+        let first_sector = self.drive.track_first_sector(track)?;
+        self.read_track_from(track, first_sector, callback)
+    }
+
+    /// Reads the tracks in `tracks` as one continuous stream, e.g. for a
+    /// gapless image of an album.
+    ///
+    /// Seeks once, to the start of the first track, and reads straight
+    /// through to the end of the last, so the engine verifies across track
+    /// boundaries just as within a track. An empty range reads nothing.
+    ///
+    /// Returns [`ErrorCode::TrackNotAudio`] if any track in the range is a
+    /// data track, before reading anything. Tracks a malformed TOC gives no
+    /// sectors are skipped, with a warning to the drive's
+    /// [message sink](CdromDrive::set_message_sink).
+    pub fn read_tracks(
+        &mut self,
+        tracks: RangeInclusive<u32>,
+        callback: extern "C" fn(c_long, c_int),
+    ) -> Result<Samples, Error> {
+        if tracks.is_empty() {
+            return Ok(Samples::cd(Vec::new()));
+        }
+        let mut sectors: Option<RangeInclusive<u64>> = None;
+        for track in tracks {
+            match self.drive.track_sector_range(track) {
+                Ok(range) => {
+                    let first = sectors.as_ref().map_or(*range.start(), |s| *s.start());
+                    sectors = Some(first..=*range.end());
+                }
+                Err(error) if error.code() == Some(ErrorCode::IllegalToc) => {
+                    self.drive
+                        .send_message(&format!("skipping track {track}: it has no sectors"));
+                }
+                Err(error) => return Err(error),
+            }
+        }
+        match sectors {
+            Some(sectors) => self.read_range(sectors, callback).map(Samples::cd),
+            None => Ok(Samples::cd(Vec::new())),
+        }
+    }
+
+    /// Reads an audio track from `start_sector` (absolute) to its end, e.g.
+    /// to resume an interrupted rip.
+    ///
+    /// Returns [`ErrorCode::TrackNotAudio`] for data tracks,
+    /// [`ErrorCode::IllegalToc`] for tracks with no sectors, and
+    /// [`ErrorCode::InvalidTrackNumber`] if `start_sector` is not inside the
+    /// track.
+    pub fn read_track_from(
+        &mut self,
+        track: u32,
+        start_sector: u64,
+        callback: extern "C" fn(c_long, c_int),
+    ) -> Result<Samples, Error> {
+        let sectors = self.drive.track_sector_range(track)?;
+        if !sectors.contains(&start_sector) {
+            return Err(Error::from_code(ErrorCode::InvalidTrackNumber));
+        }
+        self.read_range(start_sector..=*sectors.end(), callback)
+            .map(Samples::cd)
+    }
+
+    /// Reads every sector in `range` (absolute sectors, inclusive) into one
+    /// buffer of interleaved samples, regardless of which tracks the sectors
+    /// belong to.
+    ///
+    /// Returns [`ErrorCode::TrackNotAudio`] if any part of the range lies in
+    /// a data track.
+    pub fn read_range(
+        &mut self,
+        range: RangeInclusive<u64>,
+        callback: extern "C" fn(c_long, c_int),
+    ) -> Result<Vec<i16>, Error> {
+        self.read_range_with(range, Some(callback))
+    }
+
+    fn read_range_with(
+        &mut self,
+        range: RangeInclusive<u64>,
+        callback: Option<extern "C" fn(c_long, c_int)>,
+    ) -> Result<Vec<i16>, Error> {
+        let (first_sector, last_sector) = range.into_inner();
+        if first_sector > last_sector {
+            return Ok(Vec::new());
+        }
+
+        self.check_audio(first_sector, last_sector)?;
+        self.seek(SeekFrom::Start(first_sector))?;
+        let sectors = last_sector - first_sector + 1;
+        let mut samples = Vec::with_capacity(sectors as usize * CD_FRAMEWORDS as usize);
+        for _ in 0..sectors {
+            samples.extend_from_slice(self.read_checked(callback)?);
+        }
+        Ok(samples)
+    }
+
+    /// Returns [`ErrorCode::TrackNotAudio`] unless every sector from
+    /// `first_sector` to `last_sector` belongs to an audio track.
+    pub(crate) fn check_audio(&self, first_sector: u64, last_sector: u64) -> Result<(), Error> {
+        self.drive.check_audio(first_sector, last_sector)
+    }
+
+    /// Drops a pending sub-sector skip, which a read returning a whole
+    /// sector cannot honor.
+    fn discard_skip(&self) {
+        self.skip_frames.set(0);
+    }
+
+    /// Like `read`, but with an optional callback and a null check on the
+    /// returned buffer. Returns [`ErrorCode::UnaddressableSector`] if a
+    /// sub-sector skip is pending.
+    pub(crate) fn read_checked(
+        &mut self,
+        callback: Option<extern "C" fn(c_long, c_int)>,
+    ) -> Result<&[i16; CD_FRAMEWORDS as usize], Error> {
+        self.read_checked_with(&mut |position, event| {
+            if let Some(callback) = callback {
+                callback(position, event);
+            }
+        })
+    }
+
+    /// Like `read_checked`, passing the events to a closure.
+    pub(crate) fn read_checked_with(
+        &mut self,
+        callback: &mut dyn FnMut(c_long, c_int),
+    ) -> Result<&[i16; CD_FRAMEWORDS as usize], Error> {
+        if self.skip_frames.get() != 0 {
+            return Err(Error::from_code(ErrorCode::UnaddressableSector));
+        }
+        let ptr = self.read_raw_with(callback, None);
+        if ptr.is_null() {
+            return Err(Error::from_code(ErrorCode::UnknownReadError));
+        }
+        Ok(unsafe { &*(ptr as *const [i16; CD_FRAMEWORDS as usize]) })
+    }
+
+    /// Reads one sector, recording telemetry and forwarding events to
+    /// `callback`. Uses `paranoia_read_limited` if `retries` is given.
+    fn read_raw(
+        &mut self,
+        callback: Option<extern "C" fn(c_long, c_int)>,
+        retries: Option<Retries>,
+    ) -> *mut i16 {
+        self.read_raw_with(
+            &mut |position, event| {
+                if let Some(callback) = callback {
+                    callback(position, event);
+                }
+            },
+            retries,
+        )
+    }
+
+    fn read_raw_with(
+        &mut self,
+        callback: &mut dyn FnMut(c_long, c_int),
+        retries: Option<Retries>,
+    ) -> *mut i16 {
+        let raw = self.raw.as_ptr();
+        let telemetry = &mut self.telemetry;
+        *telemetry = SectorTelemetry::default();
+        let corrections = &mut self.corrections;
+        let recorder = &mut self.recorder;
+        let overlap = &self.overlap;
+        let mut sink = |position, event| {
+            telemetry.record(position, event);
+            if event as u32 == cdparanoia_sys::PARANOIA_CB_OVERLAP {
+                overlap.set(position.into());
+            }
+            if let Some(corrections) = corrections {
+                corrections.record(position, event);
+            }
+            if let Some(recorder) = recorder {
+                recorder.record(position, event);
+            }
+            callback(position, event);
+        };
+        let ptr = callback::with_sink(&mut sink, |trampoline| unsafe {
+            match retries {
+                Some(retries) if supports_read_limited() => {
+                    cdparanoia_sys::paranoia_read_limited(raw, Some(trampoline), retries.0)
+                }
+                _ => cdparanoia_sys::paranoia_read(raw, Some(trampoline)),
+            }
+        });
+        self.recovery.record(self.telemetry.is_troubled());
+        self.cursor.set(self.cursor.get().map(|cursor| cursor + 1));
+        ptr
+    }
+}
+
+/// The position `paranoia_seek` moves to, given the position before it and,
+/// for [`SeekFrom::End`], the disc's last sector.
+fn seek_target(pos: SeekFrom, previous: u64, disc_last_sector: Option<u64>) -> Option<u64> {
+    match pos {
+        SeekFrom::Start(x) => Some(x),
+        SeekFrom::Current(x) => previous.checked_add_signed(x),
+        SeekFrom::End(x) => disc_last_sector?.checked_add_signed(x),
+    }
+}
+
+/// Resolves a device path to the canonical name passed to the library,
+/// failing like a device without a drive if it cannot be resolved.
+#[cfg(unix)]
+fn canonical_device(path: &Path) -> Result<CString, FindError> {
+    use std::{os::unix::ffi::OsStrExt, path::PathBuf};
+
+    let unresolved = |error: &dyn fmt::Display| FindError {
+        error: None,
+        messages: format!("{}: {error}\n", path.display()),
+    };
+    // `components` drops trailing separators, which canonicalize would
+    // reject for a device that is not a directory.
+    let components: PathBuf = path.components().collect();
+    let canonical = std::fs::canonicalize(components).map_err(|error| unresolved(&error))?;
+    CString::new(canonical.as_os_str().as_bytes()).map_err(|error| unresolved(&error))
+}
+
+/// Drops the first `frames` stereo frames of a sector.
+fn trim_leading_frames(sector: &[i16; CD_FRAMEWORDS as usize], frames: u16) -> &[i16] {
+    &sector[usize::from(frames) * 2..]
+}
+
+impl From<CdromParanoia> for CdromDrive {
+    fn from(paranoia: CdromParanoia) -> Self {
+        paranoia.into_drive()
+    }
 }
 
 impl Drop for CdromParanoia {
@@ -464,3 +2378,415 @@ impl Drop for CdromParanoia {
         unsafe { cdparanoia_sys::paranoia_free(self.raw.as_ptr()) }
     }
 }
+
+// SAFETY: As for `CdromDrive`: the engine state is owned exclusively, and
+// only points to the drive moving along with it. The callback trampoline's
+// thread-local is only set for the duration of a read, on the reading
+// thread. Not `Sync`, since seeks through `&self` mutate the engine.
+unsafe impl Send for CdromParanoia {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn retries_round_to_engine_granularity() {
+        assert_eq!(Retries::limited(0), Retries::fewest());
+        assert_eq!(Retries::limited(5), Retries::fewest());
+        assert_eq!(Retries::limited(6), Retries(10));
+        assert_eq!(Retries::limited(20), Retries::default());
+        assert_eq!(Retries::limited(u32::MAX), Retries::unlimited());
+    }
+
+    #[test]
+    fn handles_are_send() {
+        fn assert_send<T: Send>() {}
+        assert_send::<CdromDrive>();
+        assert_send::<CdromParanoia>();
+        assert_send::<ManuallyClosed>();
+    }
+
+    #[test]
+    fn error_equality() {
+        let err = Error::from_raw(-404).unwrap_err();
+        assert_eq!(err, Error::from_code(ErrorCode::NoMediumPresent));
+        assert_eq!(err.code(), Some(ErrorCode::NoMediumPresent));
+        assert_ne!(err, Error::from_code(ErrorCode::PermissionDenied));
+        assert_eq!(Error::from_raw_long(-401), Error::from_raw(-401));
+    }
+
+    #[test]
+    fn error_codes_round_trip() {
+        for raw in (0..=10)
+            .chain(100..=103)
+            .chain(300..=300)
+            .chain(400..=405)
+            .chain(500..=500)
+        {
+            if let Some(code) = ErrorCode::from_raw(-raw) {
+                assert_eq!(code.as_raw(), raw);
+            } else {
+                assert_eq!(raw, 0);
+            }
+        }
+        assert!(ErrorCode::NoMediumPresent.is_fatal());
+        assert!(ErrorCode::PermissionDenied.is_fatal());
+        assert!(!ErrorCode::UnknownReadError.is_fatal());
+        assert!(!ErrorCode::TrackNotAudio.is_fatal());
+    }
+
+    #[test]
+    fn converts_to_io_error() {
+        let io_error = io::Error::from(Error::from_code(ErrorCode::PermissionDenied));
+        assert_eq!(io_error.kind(), io::ErrorKind::PermissionDenied);
+        assert_eq!(
+            io_error.to_string(),
+            ErrorCode::PermissionDenied.to_string()
+        );
+        let io_error = io::Error::from(Error::from_code(ErrorCode::NoMediumPresent));
+        assert_eq!(io_error.kind(), io::ErrorKind::NotFound);
+        let io_error = io::Error::from(Error::from_code(ErrorCode::TrackNotAudio));
+        assert_eq!(io_error.kind(), io::ErrorKind::Other);
+        let inner = io_error.into_inner().unwrap().downcast::<Error>().unwrap();
+        assert_eq!(*inner, Error::from_code(ErrorCode::TrackNotAudio));
+    }
+
+    #[test]
+    fn detects_permission_problems() {
+        let denied = FindError {
+            error: None,
+            messages: "/dev/sr0: Permission denied\n".to_owned(),
+        };
+        assert!(denied.permission_denied());
+        let missing = FindError {
+            error: None,
+            messages: String::new(),
+        };
+        assert!(!missing.permission_denied());
+        let denied = FindError {
+            error: Some(Error::from_code(ErrorCode::PermissionDenied)),
+            messages: String::new(),
+        };
+        assert!(denied.permission_denied());
+    }
+
+    #[test]
+    fn classifies_unidentified_drives() {
+        let denied =
+            FindError::not_found("\t\tUnable to open /dev/sr0: Permission denied\n".into());
+        assert_eq!(
+            denied.error(),
+            Some(&Error::from_code(ErrorCode::PermissionDenied))
+        );
+        let empty = FindError::not_found("/dev/sr0: No medium found\n".into());
+        assert_eq!(
+            empty.error().and_then(Error::code),
+            Some(ErrorCode::NoMediumPresent)
+        );
+        let missing = FindError::not_found("No cdrom drives accessible to you found.\n".into());
+        assert_eq!(missing.error(), None);
+        assert_eq!(missing.to_string(), "No CDROM drive found");
+    }
+
+    #[test]
+    fn parses_release() {
+        assert_eq!(
+            parse_release("cdparanoia-III-10.2 libcdparanoia release 10.2 (September 11, 2008)"),
+            Some((10, 2))
+        );
+        assert_eq!(
+            parse_release("cdparanoia-III-alpha9.8 libcdparanoia release 9.8 (March 23, 2001)"),
+            Some((9, 8))
+        );
+        assert_eq!(parse_release("libcdio-paranoia"), None);
+    }
+
+    #[test]
+    fn converts_raw_values() {
+        assert_eq!(ErrorCode::try_from(-404), Ok(ErrorCode::NoMediumPresent));
+        assert_eq!(ErrorCode::try_from(402), Ok(ErrorCode::TrackNotAudio));
+        assert_eq!(ErrorCode::try_from(11).unwrap_err().value(), 11);
+        assert_eq!(
+            Verbosity::try_from(cdparanoia_sys::CDDA_MESSAGE_LOGIT),
+            Ok(Verbosity::LogIt)
+        );
+        let error = Verbosity::try_from(7).unwrap_err();
+        assert_eq!(error.to_string(), "unknown value: 7");
+    }
+
+    #[test]
+    fn normalizes_modes() {
+        use ParanoiaMode as M;
+        // Each correction flag brings in verification on its own.
+        assert_eq!(M::REPAIR.normalized(), M::REPAIR | M::VERIFY);
+        assert_eq!(M::FRAGMENT.normalized(), M::FRAGMENT | M::VERIFY);
+        assert_eq!(
+            (M::SCRATCH | M::NEVERSKIP).normalized(),
+            M::SCRATCH | M::NEVERSKIP | M::VERIFY
+        );
+        // Overlap checking already runs the engine.
+        assert_eq!(
+            (M::REPAIR | M::OVERLAP).normalized(),
+            M::REPAIR | M::OVERLAP
+        );
+        let overlap_only = M::from_cli_flags("-Y").unwrap();
+        assert_eq!(overlap_only.normalized(), overlap_only);
+        for mode in [M::FULL, M::DISABLE, M::NEVERSKIP, M::VERIFY] {
+            assert_eq!(mode.normalized(), mode);
+        }
+    }
+
+    #[test]
+    fn verbosity_guard_restores() {
+        let fake = crate::testing::FakeDrive::new(&[(0, 0)], 100);
+        let drive = fake.drive();
+        let previous = drive.set_verbosity(Verbosity::PrintIt, Verbosity::LogIt);
+        assert_eq!(previous, (Verbosity::ForgetIt, Verbosity::ForgetIt));
+        {
+            let _guard = drive.with_verbosity(Verbosity::LogIt, Verbosity::ForgetIt);
+            assert_eq!(drive.verbosity(), (Verbosity::LogIt, Verbosity::ForgetIt));
+        }
+        assert_eq!(drive.verbosity(), (Verbosity::PrintIt, Verbosity::LogIt));
+    }
+
+    #[test]
+    fn disc_length() {
+        let fake = crate::testing::FakeDrive::new(&[(0, 0), (0, 15000)], 30001);
+        assert_eq!(fake.drive().disc_sectors(), Ok(30001));
+        assert_eq!(
+            fake.drive().disc_duration(),
+            Ok(Duration::from_secs(400) + Duration::from_nanos(13_333_333))
+        );
+    }
+
+    #[test]
+    fn read_sectors_validates_range() {
+        let mut fake = crate::testing::FakeDrive::new(&[(0, 0), (4, 100)], 200);
+        let mut buf = vec![0; 2 * CD_FRAMEWORDS as usize];
+        assert_eq!(fake.drive().read_sectors(99, 0, &mut []), Ok(0));
+        assert_eq!(
+            fake.drive().read_sectors(99, 2, &mut buf),
+            Err(Error::from_code(ErrorCode::TrackNotAudio))
+        );
+        assert_eq!(
+            fake.drive().read_sectors(250, 1, &mut buf),
+            Err(Error::from_code(ErrorCode::UnaddressableSector))
+        );
+        assert_eq!(
+            fake.drive().read_sectors(0, 3, &mut buf),
+            Err(Error::from_code(ErrorCode::BufferTooSmall))
+        );
+        fake.set_opened(false);
+        assert_eq!(
+            fake.drive().read_sectors(0, 1, &mut buf),
+            Err(Error::from_code(ErrorCode::DeviceNotOpen))
+        );
+    }
+
+    extern "C" fn ignore_event(_: c_long, _: c_int) {}
+
+    #[test]
+    fn sub_sector_seek_trims_next_read() {
+        use crate::testing::{fake_sample, FakeParanoia};
+
+        let mut fake = FakeParanoia::new(&[(0, 0)], 100);
+        let paranoia = fake.paranoia();
+        let frame = 3 * u64::from(CD_FRAMESAMPLES) + 5;
+        assert_eq!(paranoia.seek_frame(frame), Ok(3));
+        assert_eq!(paranoia.skip_frames(), 5);
+        assert_eq!(
+            paranoia.read_raw_ptr(ignore_event).err(),
+            Some(Error::from_code(ErrorCode::UnaddressableSector))
+        );
+
+        let mut buf = [0; CD_FRAMEWORDS as usize];
+        let read = paranoia.read_into(ignore_event, &mut buf);
+        assert_eq!(read, Ok(CD_FRAMEWORDS as usize - 10));
+        assert_eq!(buf[0], fake_sample(2 * frame));
+        let read = paranoia.read_into(ignore_event, &mut buf);
+        assert_eq!(read, Ok(CD_FRAMEWORDS as usize));
+        assert_eq!(buf[0], fake_sample(4 * u64::from(CD_FRAMEWORDS)));
+
+        assert_eq!(paranoia.seek_frame(2 * u64::from(CD_FRAMESAMPLES)), Ok(2));
+        let sector = paranoia.read(ignore_event);
+        assert_eq!(sector[0], fake_sample(2 * u64::from(CD_FRAMEWORDS)));
+    }
+
+    #[test]
+    fn reads_single_samples() {
+        use crate::testing::{fake_sample, FakeParanoia};
+
+        let mut fake = FakeParanoia::new(&[(0, 0)], 100);
+        let index = 3 * u64::from(CD_FRAMEWORDS) + 7;
+        assert_eq!(fake.paranoia().read_sample(index), Ok(fake_sample(index)));
+        assert_eq!(
+            fake.paranoia().read_sample(u64::MAX),
+            Err(Error::from_code(ErrorCode::UnaddressableSector))
+        );
+    }
+
+    #[test]
+    fn overlap_in_sectors_and_samples() {
+        let mut fake = crate::testing::FakeParanoia::new(&[(0, 0)], 100);
+        let paranoia = fake.paranoia();
+        assert_eq!(paranoia.overlap(), INITIAL_OVERLAP_SECTORS);
+        assert_eq!(paranoia.set_overlap(4), Ok(()));
+        assert_eq!(paranoia.overlap(), 4);
+        assert_eq!(paranoia.overlap_samples(), 4 * i64::from(CD_FRAMEWORDS));
+        assert_eq!(
+            paranoia.set_overlap(i64::MAX),
+            Err(Error::from_code(ErrorCode::OptionNotSupported))
+        );
+        assert_eq!(paranoia.overlap(), 4);
+    }
+
+    #[test]
+    fn whole_sector_read_drops_pending_skip() {
+        use crate::testing::{fake_sample, FakeParanoia};
+
+        let mut fake = FakeParanoia::new(&[(0, 0)], 100);
+        let paranoia = fake.paranoia();
+        paranoia.seek_frame(1).unwrap();
+        assert_eq!(paranoia.read(ignore_event)[0], fake_sample(0));
+        assert_eq!(paranoia.skip_frames(), 0);
+        paranoia.seek_frame(u64::from(CD_FRAMESAMPLES) + 1).unwrap();
+        assert_eq!(
+            paranoia.read_silent()[0],
+            fake_sample(u64::from(CD_FRAMEWORDS))
+        );
+        assert_eq!(paranoia.skip_frames(), 0);
+    }
+
+    #[test]
+    fn open_is_idempotent() {
+        let fake = crate::testing::FakeDrive::new(&[(0, 0)], 100);
+        // The fake has no device behind it; a real cdda_open would fail.
+        assert_eq!(fake.drive().open(), Ok(()));
+    }
+
+    #[test]
+    fn scsi_devices_only_for_scsi_interfaces() {
+        let generic = CString::new("/dev/sg1").unwrap();
+        let ioctl = CString::new("/dev/sr0").unwrap();
+        let fake = crate::testing::FakeDrive::new(&[(0, 0)], 100);
+        let raw = fake.drive().as_raw();
+        unsafe {
+            (*raw).cdda_device_name = generic.as_ptr() as *mut c_char;
+            (*raw).ioctl_device_name = ioctl.as_ptr() as *mut c_char;
+            (*raw).interface = cdparanoia_sys::SGIO_SCSI as c_int;
+        }
+        assert_eq!(
+            fake.drive().scsi_devices(),
+            Some((generic.clone(), ioctl.clone()))
+        );
+        unsafe { (*raw).interface = cdparanoia_sys::COOKED_IOCTL as c_int };
+        assert_eq!(fake.drive().scsi_devices(), None);
+        assert_eq!(fake.drive().interface(), Some(Interface::CookedIoctl));
+        unsafe { (*raw).interface = 9 };
+        assert_eq!(fake.drive().interface(), None);
+        assert_eq!(fake.drive().model(), None);
+    }
+
+    #[test]
+    fn tracks_seek_targets() {
+        assert_eq!(seek_target(SeekFrom::Start(150), 7, None), Some(150));
+        assert_eq!(seek_target(SeekFrom::Current(-2), 7, None), Some(5));
+        assert_eq!(seek_target(SeekFrom::Current(0), 7, None), Some(7));
+        assert_eq!(seek_target(SeekFrom::End(-10), 7, Some(1000)), Some(990));
+        assert_eq!(seek_target(SeekFrom::End(0), 7, None), None);
+    }
+
+    #[test]
+    fn trims_skipped_frames() {
+        let sector: [i16; CD_FRAMEWORDS as usize] = std::array::from_fn(|i| i as i16);
+        assert_eq!(trim_leading_frames(&sector, 0).len(), 1176);
+        let trimmed = trim_leading_frames(&sector, 587);
+        assert_eq!(trimmed, [1174, 1175]);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn canonicalizes_device_paths() {
+        let dir = std::env::temp_dir().join(format!("cdparanoia-dev-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let device = dir.join("sr0");
+        let link = dir.join("cdrom");
+        std::fs::write(&device, b"").unwrap();
+        let _ = std::fs::remove_file(&link);
+        std::os::unix::fs::symlink(&device, &link).unwrap();
+
+        let expected = std::fs::canonicalize(&device).unwrap();
+        let expected = CString::new(expected.to_str().unwrap()).unwrap();
+        assert_eq!(canonical_device(&link).unwrap(), expected);
+        assert_eq!(canonical_device(&dir.join("./sr0")).unwrap(), expected);
+        let trailing = format!("{}/", link.display());
+        assert_eq!(canonical_device(Path::new(&trailing)).unwrap(), expected);
+        assert!(canonical_device(&dir.join("missing")).is_err());
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn flushes_messages_into_sink() {
+        let mut fake = crate::testing::FakeDrive::new(&[(0, 0)], 100);
+        let drive = fake.drive_mut();
+        // The library hands over malloc'd buffers, which CddaString frees.
+        unsafe {
+            (*drive.as_raw()).messagebuf = libc::strdup(b"opened\n\0".as_ptr().cast());
+            (*drive.as_raw()).errorbuf = libc::strdup(b"read error\n\0".as_ptr().cast());
+        }
+        let (sender, receiver) = std::sync::mpsc::channel();
+        drive.set_message_sink(move |log| sender.send(log.to_owned()).unwrap());
+        drive.flush_messages();
+        assert_eq!(
+            receiver.try_iter().collect::<Vec<_>>(),
+            ["opened\n", "read error\n"]
+        );
+
+        drive.flush_messages();
+        assert_eq!(receiver.try_recv().ok(), None);
+        drive.clear_message_sink();
+    }
+
+    #[test]
+    fn clears_pending_messages() {
+        let fake = crate::testing::FakeDrive::new(&[(0, 0)], 100);
+        let drive = fake.drive();
+        assert!(!drive.has_pending_messages());
+        unsafe { (*drive.as_raw()).errorbuf = libc::strdup(b"read error\n\0".as_ptr().cast()) };
+        assert!(drive.has_pending_messages());
+        drive.clear_messages();
+        assert!(!drive.has_pending_messages());
+        assert!(drive.errors().is_none());
+    }
+
+    #[test]
+    fn open_with_sink_delivers_and_restores() {
+        let fake = crate::testing::FakeDrive::new(&[(0, 0)], 100);
+        let drive = fake.drive();
+        drive.set_verbosity(Verbosity::PrintIt, Verbosity::ForgetIt);
+        unsafe { (*drive.as_raw()).messagebuf = libc::strdup(b"opened\n\0".as_ptr().cast()) };
+        let (mut messages, mut errors) = (Vec::new(), Vec::new());
+        drive
+            .open_with_sink(
+                |log| messages.push(log.to_owned()),
+                |log| errors.push(log.to_owned()),
+            )
+            .unwrap();
+        assert_eq!(messages, ["opened\n"]);
+        assert!(errors.is_empty());
+        assert_eq!(drive.verbosity(), (Verbosity::PrintIt, Verbosity::ForgetIt));
+    }
+
+    #[test]
+    fn manually_closed_drop_keeps_drive() {
+        let fake = crate::testing::FakeDrive::new(&[(0, 0)], 100);
+        {
+            let shared = unsafe { CdromDrive::from_raw(fake.drive().as_raw()).unwrap() };
+            let shared = shared.manually_closed();
+            assert!(shared.is_open());
+            // Dropping must not reach cdda_close, which would free memory the
+            // library never allocated.
+        }
+        assert!(fake.drive().is_open());
+    }
+}
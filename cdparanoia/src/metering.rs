@@ -0,0 +1,153 @@
+//! Peak and RMS level measurement.
+
+use std::ffi::{c_int, c_long};
+
+use crate::{CdromParanoia, Error, Samples};
+
+/// Peak and RMS level of some audio, in sample units.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct PeakRms {
+    /// Largest absolute sample value. This is a `u16` so that a full-scale
+    /// negative sample (32768) is representable.
+    pub peak: u16,
+    pub rms: f64,
+}
+
+#[derive(Debug, Clone, Copy, Default)]
+struct Accumulator {
+    peak: u16,
+    sum_of_squares: u128,
+    count: u64,
+}
+
+impl Accumulator {
+    fn add(&mut self, sample: i16) {
+        self.peak = self.peak.max(sample.unsigned_abs());
+        self.sum_of_squares += (i32::from(sample) * i32::from(sample)) as u128;
+        self.count += 1;
+    }
+
+    fn merge(&mut self, other: &Self) {
+        self.peak = self.peak.max(other.peak);
+        self.sum_of_squares += other.sum_of_squares;
+        self.count += other.count;
+    }
+
+    fn result(&self) -> PeakRms {
+        let rms = if self.count == 0 {
+            0.0
+        } else {
+            (self.sum_of_squares as f64 / self.count as f64).sqrt()
+        };
+        PeakRms {
+            peak: self.peak,
+            rms,
+        }
+    }
+}
+
+/// Accumulates [`PeakRms`] levels over interleaved samples fed in any
+/// number of pieces.
+#[derive(Debug, Clone)]
+pub struct Meter {
+    channels: Vec<Accumulator>,
+    /// Channel of the next sample fed.
+    next_channel: usize,
+}
+
+impl Meter {
+    /// # Panics
+    ///
+    /// If `channels` is zero.
+    pub fn new(channels: usize) -> Self {
+        assert!(channels > 0, "channel count must be non-zero");
+        Self {
+            channels: vec![Accumulator::default(); channels],
+            next_channel: 0,
+        }
+    }
+
+    /// Adds interleaved samples. Pieces need not end on a frame boundary.
+    pub fn feed(&mut self, samples: &[i16]) {
+        for &sample in samples {
+            self.channels[self.next_channel].add(sample);
+            self.next_channel = (self.next_channel + 1) % self.channels.len();
+        }
+    }
+
+    /// Levels of one channel.
+    pub fn channel(&self, channel: usize) -> PeakRms {
+        self.channels[channel].result()
+    }
+
+    /// Levels of every channel, in order.
+    pub fn per_channel(&self) -> Vec<PeakRms> {
+        self.channels.iter().map(Accumulator::result).collect()
+    }
+
+    /// Levels over all channels together.
+    pub fn total(&self) -> PeakRms {
+        let mut total = Accumulator::default();
+        for channel in &self.channels {
+            total.merge(channel);
+        }
+        total.result()
+    }
+}
+
+impl CdromParanoia {
+    /// Like [`read_track`](Self::read_track), also measuring the levels of
+    /// the track over both channels.
+    ///
+    /// Use a [`Meter`] directly for a per-channel breakdown.
+    pub fn read_track_metered(
+        &mut self,
+        track: u32,
+        callback: extern "C" fn(c_long, c_int),
+    ) -> Result<(Samples, PeakRms), Error> {
+        let samples = self.read_track(track, callback)?;
+        let mut meter = Meter::new(samples.channels().into());
+        meter.feed(&samples);
+        Ok((samples, meter.total()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn square_wave_levels() {
+        let mut meter = Meter::new(2);
+        // Left: full-scale square wave, right: silence.
+        let samples: Vec<i16> = (0..1000)
+            .flat_map(|i| [if i % 2 == 0 { i16::MIN } else { i16::MAX }, 0])
+            .collect();
+        // Uneven pieces, splitting frames.
+        meter.feed(&samples[..3]);
+        meter.feed(&samples[3..]);
+
+        let left = meter.channel(0);
+        assert_eq!(left.peak, 32768);
+        assert!((left.rms - 32767.5).abs() < 0.01);
+        assert_eq!(meter.channel(1), PeakRms::default());
+
+        let total = meter.total();
+        assert_eq!(total.peak, 32768);
+        assert!((total.rms - 32767.5 / 2f64.sqrt()).abs() < 0.01);
+        assert_eq!(meter.per_channel().len(), 2);
+    }
+
+    #[test]
+    fn constant_amplitude() {
+        let mut meter = Meter::new(1);
+        meter.feed(&[-100, 100, -100, 100]);
+        assert_eq!(
+            meter.total(),
+            PeakRms {
+                peak: 100,
+                rms: 100.0
+            }
+        );
+    }
+}
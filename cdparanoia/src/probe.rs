@@ -0,0 +1,95 @@
+//! Choosing among several drives.
+
+use std::path::{Path, PathBuf};
+
+use crate::{CdromDrive, Error, Verbosity};
+
+/// A drive found by [`CdromDrive::find_best`], for the selection closure to
+/// judge.
+pub struct ProbeReport<'a> {
+    /// The canonical device node the drive was identified from.
+    pub device: &'a Path,
+    /// The drive, opened if [`open`](Self::open) is `Ok`. Any of its
+    /// queries can be used to judge it.
+    pub drive: &'a CdromDrive,
+    /// The result of opening the drive, e.g.
+    /// [`ErrorCode::NoMediumPresent`](crate::ErrorCode::NoMediumPresent)
+    /// without a disc.
+    pub open: Result<(), Error>,
+    /// Whether the drive reports accurate audio streaming, see
+    /// [`CdromDrive::accurate_stream`]. `false` if it could not be asked.
+    #[cfg(target_os = "linux")]
+    pub accurate_stream: bool,
+}
+
+impl<'a> ProbeReport<'a> {
+    fn new(device: &'a Path, drive: &'a CdromDrive, open: Result<(), Error>) -> Self {
+        Self {
+            device,
+            drive,
+            open,
+            #[cfg(target_os = "linux")]
+            accurate_stream: drive.accurate_stream().unwrap_or(false),
+        }
+    }
+}
+
+impl CdromDrive {
+    /// Identifies and opens every drive in turn, returning the first for
+    /// which `accept` returns `true`.
+    ///
+    /// The nodes tried are `/dev/cdrom`, then `/dev/sr0` to `/dev/sr9`,
+    /// `/dev/scd0` to `/dev/scd9` and `/dev/cdroms/cdrom0` to
+    /// `/dev/cdroms/cdrom9`, skipping missing ones and aliases of nodes
+    /// already tried. Probing is not free: opening a drive with a disc
+    /// spins it up, reads its TOC and reads audio to detect its byte order,
+    /// which can take several seconds per drive. Rejected drives are closed
+    /// before the next one is probed.
+    pub fn find_best(accept: impl Fn(&ProbeReport) -> bool) -> Option<Self> {
+        probed_drives().find_map(|(device, drive, open)| {
+            let report = ProbeReport::new(&device, &drive, open);
+            accept(&report).then_some(drive)
+        })
+    }
+
+    /// Like [`find_best`](Self::find_best), but probes every drive and
+    /// returns the one with the highest score. Drives scored `None` are
+    /// rejected; among equal scores the first found wins.
+    ///
+    /// Only the best drive so far is kept open while the rest are probed.
+    pub fn find_best_by<K: Ord>(score: impl Fn(&ProbeReport) -> Option<K>) -> Option<Self> {
+        let mut best: Option<(K, Self)> = None;
+        for (device, drive, open) in probed_drives() {
+            let report = ProbeReport::new(&device, &drive, open);
+            let Some(key) = score(&report) else {
+                continue;
+            };
+            if best.as_ref().map_or(true, |(best_key, _)| key > *best_key) {
+                best = Some((key, drive));
+            }
+        }
+        best.map(|(_, drive)| drive)
+    }
+}
+
+/// Identifies and opens the drive behind each candidate node, yielding the
+/// canonical node, the drive and the result of opening it.
+fn probed_drives() -> impl Iterator<Item = (PathBuf, CdromDrive, Result<(), Error>)> {
+    let numbered = |prefix: &'static str| (0..10).map(move |n| format!("{prefix}{n}"));
+    let candidates = std::iter::once("/dev/cdrom".to_owned())
+        .chain(numbered("/dev/sr"))
+        .chain(numbered("/dev/scd"))
+        .chain(numbered("/dev/cdroms/cdrom"));
+
+    let mut seen = Vec::new();
+    candidates.filter_map(move |candidate| {
+        let device = std::fs::canonicalize(candidate).ok()?;
+        if seen.contains(&device) {
+            return None;
+        }
+        seen.push(device.clone());
+        let drive = CdromDrive::identify_path(&device, Verbosity::ForgetIt).ok()?;
+        let open = drive.open();
+        Some((device, drive, open))
+    })
+}
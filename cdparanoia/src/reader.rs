@@ -0,0 +1,232 @@
+//! `std::io` adapter over verified paranoia reads.
+
+use std::{
+    ffi::{c_int, c_long},
+    io::{self, Read, Seek, SeekFrom, Write},
+    ops::RangeInclusive,
+};
+
+use crate::{sector_bytes, CdromParanoia, Endianness, Error, CD_FRAMESIZE_RAW};
+
+const SECTOR_BYTES: u64 = CD_FRAMESIZE_RAW as u64;
+
+/// Reads a range of sectors through the paranoia engine as a stream of
+/// 16-bit PCM bytes, little-endian unless set otherwise with
+/// [`set_endianness`](Self::set_endianness).
+///
+/// Byte 0 of the stream is the first sample of the first sector in the
+/// range. Sectors are read lazily, one at a time, so seeking is cheap until
+/// the next read. Reading stops with EOF after the last sector of the
+/// range. Failed reads return the library's [`Error`] as an [`io::Error`],
+/// with the kind from its `From` conversion.
+pub struct ParanoiaReader {
+    paranoia: CdromParanoia,
+    first_sector: u64,
+    last_sector: u64,
+    /// Byte offset of the next byte to return, relative to `first_sector`.
+    pos: u64,
+    /// Sector the paranoia engine will return on its next read.
+    cursor: Option<u64>,
+    /// Sector currently held in `buffer`.
+    buffered: Option<u64>,
+    buffer: [u8; CD_FRAMESIZE_RAW as usize],
+    endianness: Endianness,
+}
+
+impl ParanoiaReader {
+    /// Creates a reader over the whole disc, from `disc_first_sector` to
+    /// `disc_last_sector`.
+    ///
+    /// Reading sectors that belong to data tracks does not produce audio;
+    /// use [`with_range`](Self::with_range) to restrict the stream.
+    pub fn new(paranoia: CdromParanoia) -> Result<Self, Error> {
+        let first_sector = paranoia.drive().disc_first_sector()?;
+        let last_sector = paranoia.drive().disc_last_sector()?;
+        Ok(Self::with_range(paranoia, first_sector..=last_sector))
+    }
+
+    /// Creates a reader over the given absolute sectors (inclusive).
+    pub fn with_range(paranoia: CdromParanoia, range: RangeInclusive<u64>) -> Self {
+        let (first_sector, last_sector) = range.into_inner();
+        Self {
+            paranoia,
+            first_sector,
+            last_sector,
+            pos: 0,
+            cursor: None,
+            buffered: None,
+            buffer: [0; CD_FRAMESIZE_RAW as usize],
+            endianness: Endianness::default(),
+        }
+    }
+
+    pub fn paranoia(&self) -> &CdromParanoia {
+        &self.paranoia
+    }
+
+    pub fn into_inner(self) -> CdromParanoia {
+        self.paranoia
+    }
+
+    pub fn endianness(&self) -> Endianness {
+        self.endianness
+    }
+
+    /// Sets the byte order of the samples returned from now on. May be
+    /// changed mid-stream; the current sector is not re-read.
+    pub fn set_endianness(&mut self, endianness: Endianness) {
+        if endianness != self.endianness {
+            self.endianness = endianness;
+            for bytes in self.buffer.chunks_exact_mut(2) {
+                bytes.swap(0, 1);
+            }
+        }
+    }
+
+    /// Total length of the stream in bytes.
+    pub fn len(&self) -> u64 {
+        if self.last_sector < self.first_sector {
+            0
+        } else {
+            (self.last_sector - self.first_sector + 1) * SECTOR_BYTES
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Bytes left until the end of the stream, e.g. to preallocate the
+    /// output.
+    pub fn bytes_remaining(&self) -> u64 {
+        self.len().saturating_sub(self.pos)
+    }
+
+    fn fill(&mut self, sector: u64) -> Result<(), Error> {
+        if self.cursor != Some(sector) {
+            self.paranoia.seek(SeekFrom::Start(sector))?;
+        }
+        // Invalidate first, so a failed read never leaves stale data behind.
+        self.buffered = None;
+        self.cursor = None;
+        let samples = self.paranoia.read_checked(None)?;
+        self.buffer = sector_bytes(samples, self.endianness);
+        self.buffered = Some(sector);
+        self.cursor = Some(sector + 1);
+        Ok(())
+    }
+}
+
+impl Read for ParanoiaReader {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        if buf.is_empty() || self.pos >= self.len() {
+            return Ok(0);
+        }
+        let sector = self.first_sector + self.pos / SECTOR_BYTES;
+        let offset = (self.pos % SECTOR_BYTES) as usize;
+        if self.buffered != Some(sector) {
+            self.fill(sector)?;
+        }
+
+        let available = &self.buffer[offset..];
+        let n = available.len().min(buf.len());
+        buf[..n].copy_from_slice(&available[..n]);
+        self.pos += n as u64;
+        Ok(n)
+    }
+}
+
+impl Seek for ParanoiaReader {
+    /// Seeks to a byte offset in the stream. Seeking past the end of the
+    /// range returns an [`io::ErrorKind::InvalidInput`] error; seeking
+    /// exactly to the end is allowed and makes the next read return EOF.
+    fn seek(&mut self, pos: SeekFrom) -> io::Result<u64> {
+        self.pos = resolve_seek(self.pos, self.len(), pos)?;
+        Ok(self.pos)
+    }
+}
+
+impl CdromParanoia {
+    /// Streams an audio track into `out` as 16-bit PCM bytes in the given
+    /// byte order, writing each sector as soon as it is verified, and
+    /// returns the number of bytes written.
+    ///
+    /// Unlike [`read_track`](Self::read_track) the track is never held in
+    /// memory as a whole, so `out` can be a pipe, socket or encoder.
+    /// Library errors are returned as [`io::Error`]s with the kind from
+    /// the [`Error`] conversion. What was written before an error is left
+    /// in `out`.
+    pub fn read_track_to_writer(
+        &mut self,
+        track: u32,
+        out: &mut impl Write,
+        endianness: Endianness,
+        callback: extern "C" fn(c_long, c_int),
+    ) -> io::Result<u64> {
+        let sectors = self.drive().track_sector_range(track)?;
+        self.seek(SeekFrom::Start(*sectors.start()))?;
+        let mut written = 0;
+        for _ in sectors {
+            let samples = self.read_checked(Some(callback))?;
+            out.write_all(&sector_bytes(samples, endianness))?;
+            written += SECTOR_BYTES;
+        }
+        Ok(written)
+    }
+}
+
+fn resolve_seek(current: u64, len: u64, pos: SeekFrom) -> io::Result<u64> {
+    let target = match pos {
+        SeekFrom::Start(x) => Some(x),
+        SeekFrom::End(x) => len.checked_add_signed(x),
+        SeekFrom::Current(x) => current.checked_add_signed(x),
+    };
+    match target {
+        Some(target) if target <= len => Ok(target),
+        _ => Err(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            "seek outside of the sector range",
+        )),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn mid_sector_seeks() {
+        let len = 3 * SECTOR_BYTES;
+        assert_eq!(resolve_seek(0, len, SeekFrom::Start(1000)).unwrap(), 1000);
+        assert_eq!(
+            resolve_seek(1000, len, SeekFrom::Current(SECTOR_BYTES as i64)).unwrap(),
+            1000 + SECTOR_BYTES
+        );
+        assert_eq!(resolve_seek(0, len, SeekFrom::End(-10)).unwrap(), len - 10);
+        assert_eq!(resolve_seek(5, len, SeekFrom::Current(-5)).unwrap(), 0);
+    }
+
+    #[test]
+    fn seeks_outside_range() {
+        let len = 2 * SECTOR_BYTES;
+        assert_eq!(resolve_seek(0, len, SeekFrom::End(0)).unwrap(), len);
+        assert!(resolve_seek(0, len, SeekFrom::End(1)).is_err());
+        assert!(resolve_seek(0, len, SeekFrom::Start(len + 1)).is_err());
+        assert!(resolve_seek(3, len, SeekFrom::Current(-4)).is_err());
+    }
+
+    #[test]
+    fn streams_track_in_requested_byte_order() {
+        extern "C" fn ignore_event(_: c_long, _: c_int) {}
+
+        let mut fake = crate::testing::FakeParanoia::new(&[(0, 0), (0, 2)], 4);
+        let mut out = Vec::new();
+        let written = fake
+            .paranoia()
+            .read_track_to_writer(1, &mut out, Endianness::Big, ignore_event)
+            .unwrap();
+        assert_eq!(written, 2 * SECTOR_BYTES);
+        assert_eq!(out.len() as u64, written);
+        assert_eq!(out[2..4], crate::testing::fake_sample(1).to_be_bytes());
+    }
+}
@@ -0,0 +1,256 @@
+//! Recording of the paranoia callback stream for later analysis.
+
+use std::{
+    cmp::Reverse,
+    collections::{BTreeMap, VecDeque},
+    ffi::{c_int, c_long},
+    ops::RangeInclusive,
+    time::{Duration, Instant},
+};
+
+use crate::{CdromParanoia, CD_FRAMEWORDS};
+
+/// Most problem regions listed by [`EventRecorder::summary`].
+const WORST_REGIONS: usize = 10;
+
+/// One callback event, as passed to the read callback.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RecordedEvent {
+    /// Time since the recorder was created.
+    pub elapsed: Duration,
+    /// Position in samples from the start of the disc.
+    pub position: c_long,
+    /// One of the `PARANOIA_CB_*` codes.
+    pub event: c_int,
+}
+
+impl RecordedEvent {
+    /// The absolute sector the event's position falls in, if it is not
+    /// negative.
+    pub fn sector(&self) -> Option<u64> {
+        u64::try_from(self.position)
+            .ok()
+            .map(|position| position / CD_FRAMEWORDS as u64)
+    }
+}
+
+/// Records the callback events of every read of a session, see
+/// [`CdromParanoia::set_event_recorder`], e.g. to attach to a bug report
+/// about a troublesome disc or drive.
+#[derive(Debug, Clone)]
+pub struct EventRecorder {
+    start: Instant,
+    events: VecDeque<RecordedEvent>,
+    /// Most events kept, if bounded.
+    limit: Option<usize>,
+    dropped: u64,
+}
+
+impl Default for EventRecorder {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl EventRecorder {
+    /// Creates a recorder that keeps every event.
+    pub fn new() -> Self {
+        Self {
+            start: Instant::now(),
+            events: VecDeque::new(),
+            limit: None,
+            dropped: 0,
+        }
+    }
+
+    /// Creates a recorder that keeps only the last `limit` events,
+    /// discarding older ones as new ones arrive.
+    pub fn bounded(limit: usize) -> Self {
+        Self {
+            limit: Some(limit),
+            ..Self::new()
+        }
+    }
+
+    /// Records an event, timestamped now.
+    pub fn record(&mut self, position: c_long, event: c_int) {
+        if self.limit == Some(0) {
+            self.dropped += 1;
+            return;
+        }
+        if self.limit == Some(self.events.len()) {
+            self.events.pop_front();
+            self.dropped += 1;
+        }
+        self.events.push_back(RecordedEvent {
+            elapsed: self.start.elapsed(),
+            position,
+            event,
+        });
+    }
+
+    /// The kept events, oldest first.
+    pub fn events(&self) -> impl Iterator<Item = &RecordedEvent> + '_ {
+        self.events.iter()
+    }
+
+    /// Number of events discarded by a bounded recorder.
+    pub fn dropped(&self) -> u64 {
+        self.dropped
+    }
+
+    /// Feeds the kept events, oldest first, to `callback` as the engine
+    /// did, e.g. to run them through a different analysis.
+    pub fn replay(&self, mut callback: impl FnMut(c_long, c_int)) {
+        for event in &self.events {
+            callback(event.position, event.event);
+        }
+    }
+
+    /// Counts the kept events and finds the regions with the most problems.
+    pub fn summary(&self) -> EventSummary {
+        let mut counts = BTreeMap::new();
+        let mut problems = BTreeMap::new();
+        for event in &self.events {
+            *counts.entry(event.event).or_insert(0) += 1;
+            if is_problem(event.event) {
+                if let Some(sector) = event.sector() {
+                    *problems.entry(sector).or_insert(0) += 1;
+                }
+            }
+        }
+
+        let mut regions: Vec<ProblemRegion> = Vec::new();
+        for (sector, count) in problems {
+            match regions.last_mut() {
+                Some(region) if *region.sectors.end() + 1 == sector => {
+                    region.sectors = *region.sectors.start()..=sector;
+                    region.problems += count;
+                }
+                _ => regions.push(ProblemRegion {
+                    sectors: sector..=sector,
+                    problems: count,
+                }),
+            }
+        }
+        // Stable, so equally bad regions stay in disc order.
+        regions.sort_by_key(|region| Reverse(region.problems));
+        regions.truncate(WORST_REGIONS);
+
+        EventSummary {
+            counts,
+            worst_regions: regions,
+        }
+    }
+}
+
+/// Statistics over the events of an [`EventRecorder`].
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct EventSummary {
+    /// Number of events of each kind, by `PARANOIA_CB_*` code.
+    pub counts: BTreeMap<c_int, u64>,
+    /// Runs of adjacent sectors with read errors, skips, drift, backoffs,
+    /// scratches or repairs, most problems first. At most ten are listed.
+    pub worst_regions: Vec<ProblemRegion>,
+}
+
+/// Adjacent sectors that had problem events, see [`EventSummary`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ProblemRegion {
+    /// Absolute sectors, inclusive.
+    pub sectors: RangeInclusive<u64>,
+    /// Number of problem events in the region.
+    pub problems: u64,
+}
+
+fn is_problem(event: c_int) -> bool {
+    matches!(
+        event as u32,
+        cdparanoia_sys::PARANOIA_CB_READERR
+            | cdparanoia_sys::PARANOIA_CB_SKIP
+            | cdparanoia_sys::PARANOIA_CB_DRIFT
+            | cdparanoia_sys::PARANOIA_CB_BACKOFF
+            | cdparanoia_sys::PARANOIA_CB_SCRATCH
+            | cdparanoia_sys::PARANOIA_CB_REPAIR
+    )
+}
+
+impl CdromParanoia {
+    /// Starts recording the callback events of every read into `recorder`,
+    /// in addition to passing them to the read's callback. Replaces any
+    /// previous recorder.
+    pub fn set_event_recorder(&mut self, recorder: EventRecorder) {
+        self.recorder = Some(recorder);
+    }
+
+    pub fn event_recorder(&self) -> Option<&EventRecorder> {
+        self.recorder.as_ref()
+    }
+
+    /// Stops recording and returns the recorder, if one was set.
+    pub fn take_event_recorder(&mut self) -> Option<EventRecorder> {
+        self.recorder.take()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SECTOR: c_long = CD_FRAMEWORDS as c_long;
+
+    #[test]
+    fn bounded_recorder_keeps_latest() {
+        let mut recorder = EventRecorder::bounded(2);
+        for position in 0..5 {
+            recorder.record(position, cdparanoia_sys::PARANOIA_CB_READ as c_int);
+        }
+        let positions: Vec<_> = recorder.events().map(|event| event.position).collect();
+        assert_eq!(positions, [3, 4]);
+        assert_eq!(recorder.dropped(), 3);
+
+        let mut replayed = Vec::new();
+        recorder.replay(|position, _| replayed.push(position));
+        assert_eq!(replayed, [3, 4]);
+    }
+
+    #[test]
+    fn summarizes_problem_regions() {
+        use cdparanoia_sys::{PARANOIA_CB_READ, PARANOIA_CB_READERR, PARANOIA_CB_SKIP};
+
+        let mut recorder = EventRecorder::new();
+        let events = [
+            (10, PARANOIA_CB_READ),
+            (10, PARANOIA_CB_READERR),
+            (11, PARANOIA_CB_SKIP),
+            (50, PARANOIA_CB_READERR),
+            (50, PARANOIA_CB_READERR),
+            (51, PARANOIA_CB_READERR),
+            (90, PARANOIA_CB_SKIP),
+        ];
+        for (sector, event) in events {
+            recorder.record(sector * SECTOR + 3, event as c_int);
+        }
+
+        let summary = recorder.summary();
+        assert_eq!(summary.counts[&(PARANOIA_CB_READERR as c_int)], 4);
+        assert_eq!(summary.counts[&(PARANOIA_CB_READ as c_int)], 1);
+        assert_eq!(
+            summary.worst_regions,
+            [
+                ProblemRegion {
+                    sectors: 50..=51,
+                    problems: 3
+                },
+                ProblemRegion {
+                    sectors: 10..=11,
+                    problems: 2
+                },
+                ProblemRegion {
+                    sectors: 90..=90,
+                    problems: 1
+                },
+            ]
+        );
+    }
+}
@@ -0,0 +1,179 @@
+//! Sample rate conversion of ripped audio.
+
+use std::io::{self, Read};
+
+use crate::{Endianness, ParanoiaReader};
+
+/// CD audio sample rate.
+const SOURCE_HZ: u64 = 44100;
+
+/// Converts a stream of 16-bit little-endian stereo PCM at 44100 Hz to
+/// another sample rate, producing the same format.
+///
+/// Uses linear interpolation between neighbouring frames. This is cheap and
+/// has no latency, but does not filter: downsampling aliases content above
+/// the new Nyquist frequency, and upsampling slightly dulls high
+/// frequencies. Use a dedicated resampler when quality matters.
+pub struct Resampler<R> {
+    inner: R,
+    target_hz: u64,
+    /// Index of the next output frame.
+    output_frame: u64,
+    /// Index of the source frame in `current`.
+    source_frame: u64,
+    current: Option<[i16; 2]>,
+    next: Option<[i16; 2]>,
+    started: bool,
+    pending: [u8; 4],
+    pending_len: usize,
+}
+
+impl<R: Read> Resampler<R> {
+    pub fn new(inner: R, target_hz: u32) -> Self {
+        assert!(target_hz > 0, "target sample rate must be non-zero");
+        Self {
+            inner,
+            target_hz: target_hz.into(),
+            output_frame: 0,
+            source_frame: 0,
+            current: None,
+            next: None,
+            started: false,
+            pending: [0; 4],
+            pending_len: 0,
+        }
+    }
+
+    pub fn into_inner(self) -> R {
+        self.inner
+    }
+
+    fn read_frame(&mut self) -> io::Result<Option<[i16; 2]>> {
+        let mut bytes = [0; 4];
+        let mut filled = 0;
+        while filled < bytes.len() {
+            match self.inner.read(&mut bytes[filled..]) {
+                Ok(0) => return Ok(None),
+                Ok(n) => filled += n,
+                Err(err) if err.kind() == io::ErrorKind::Interrupted => {}
+                Err(err) => return Err(err),
+            }
+        }
+        Ok(Some([
+            i16::from_le_bytes([bytes[0], bytes[1]]),
+            i16::from_le_bytes([bytes[2], bytes[3]]),
+        ]))
+    }
+
+    /// Computes the next output frame, or `None` at the end of the input.
+    fn next_output(&mut self) -> io::Result<Option<[i16; 2]>> {
+        if !self.started {
+            self.current = self.read_frame()?;
+            self.next = self.read_frame()?;
+            self.started = true;
+        }
+
+        let position = self.output_frame * SOURCE_HZ;
+        let index = position / self.target_hz;
+        let fraction = (position % self.target_hz) as f64 / self.target_hz as f64;
+        while self.source_frame < index {
+            self.current = self.next.take();
+            self.next = self.read_frame()?;
+            self.source_frame += 1;
+        }
+
+        let frame = match (self.current, self.next) {
+            (Some(a), Some(b)) => {
+                let lerp = |a: i16, b: i16| (a as f64 + (b as f64 - a as f64) * fraction).round();
+                [lerp(a[0], b[0]) as i16, lerp(a[1], b[1]) as i16]
+            }
+            // Past the last frame, only an exact hit can be produced.
+            (Some(a), None) if fraction == 0.0 => a,
+            _ => return Ok(None),
+        };
+        self.output_frame += 1;
+        Ok(Some(frame))
+    }
+}
+
+impl<R: Read> Read for Resampler<R> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        if self.pending_len == 0 {
+            let Some([left, right]) = self.next_output()? else {
+                return Ok(0);
+            };
+            self.pending[..2].copy_from_slice(&left.to_le_bytes());
+            self.pending[2..].copy_from_slice(&right.to_le_bytes());
+            self.pending_len = self.pending.len();
+        }
+        let start = self.pending.len() - self.pending_len;
+        let n = self.pending_len.min(buf.len());
+        buf[..n].copy_from_slice(&self.pending[start..start + n]);
+        self.pending_len -= n;
+        Ok(n)
+    }
+}
+
+impl ParanoiaReader {
+    /// Wraps the reader in a [`Resampler`] producing `target_hz` audio.
+    ///
+    /// The resampler works on little-endian input, so the reader is switched
+    /// to [`Endianness::Little`].
+    pub fn resampled(mut self, target_hz: u32) -> Resampler<Self> {
+        self.set_endianness(Endianness::Little);
+        Resampler::new(self, target_hz)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::Cursor;
+
+    use super::*;
+
+    fn pcm(frames: &[[i16; 2]]) -> Cursor<Vec<u8>> {
+        let bytes = frames
+            .iter()
+            .flat_map(|frame| frame.iter().flat_map(|sample| sample.to_le_bytes()))
+            .collect();
+        Cursor::new(bytes)
+    }
+
+    fn frames(mut reader: impl Read) -> Vec<[i16; 2]> {
+        let mut bytes = Vec::new();
+        reader.read_to_end(&mut bytes).unwrap();
+        bytes
+            .chunks_exact(4)
+            .map(|b| {
+                [
+                    i16::from_le_bytes([b[0], b[1]]),
+                    i16::from_le_bytes([b[2], b[3]]),
+                ]
+            })
+            .collect()
+    }
+
+    #[test]
+    fn same_rate_is_identity() {
+        let input = [[1, -1], [2, -2], [3, -3]];
+        assert_eq!(frames(Resampler::new(pcm(&input), 44100)), input);
+    }
+
+    #[test]
+    fn doubling_interpolates_midpoints() {
+        let input = [[0, 100], [10, 0], [20, -100]];
+        assert_eq!(
+            frames(Resampler::new(pcm(&input), 88200)),
+            [[0, 100], [5, 50], [10, 0], [15, -50], [20, -100]]
+        );
+    }
+
+    #[test]
+    fn halving_drops_frames() {
+        let input = [[0, 0], [1, 1], [2, 2], [3, 3], [4, 4]];
+        assert_eq!(
+            frames(Resampler::new(pcm(&input), 22050)),
+            [[0, 0], [2, 2], [4, 4]]
+        );
+    }
+}
@@ -0,0 +1,950 @@
+//! Batch reading of sector ranges.
+
+use std::{
+    ffi::{c_int, c_long},
+    io::SeekFrom,
+    ops::{Range, RangeInclusive},
+};
+
+use crate::{
+    CdromDrive, CdromParanoia, Concealment, CorrectionMap, Error, ErrorCode, ParanoiaEvent,
+    ParanoiaMode, RipStats, Samples, SectorTelemetry, CD_FRAMEWORDS,
+};
+
+/// Settings for batch reads.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RipConfig {
+    /// Sectors read per step of [`CdromParanoia::read_chunks`].
+    ///
+    /// Small chunks yield often, e.g. for frequent progress updates; large
+    /// chunks have less per-step overhead. This is independent of how many
+    /// sectors the drive reads per request. Defaults to 1; 0 is treated as
+    /// 1.
+    pub read_chunk_sectors: u32,
+
+    /// Lower the drive speed while it struggles, see
+    /// [`adaptive_speed`](Self::adaptive_speed). Defaults to `false`.
+    pub adaptive_speed: bool,
+
+    /// Trim near-silence from the edges of ripped tracks, see
+    /// [`trim_silence`](Self::trim_silence). Defaults to `None`, keeping
+    /// the audio exact.
+    pub trim_silence: Option<i16>,
+
+    /// Read every track twice and compare, see
+    /// [`double_read_verify`](Self::double_read_verify). Defaults to
+    /// `false`.
+    pub double_read_verify: bool,
+
+    /// How to fill in sectors the engine skipped, see
+    /// [`concealment`](Self::concealment). Defaults to
+    /// [`Concealment::Silence`].
+    pub concealment: Concealment,
+}
+
+impl Default for RipConfig {
+    fn default() -> Self {
+        Self {
+            read_chunk_sectors: 1,
+            adaptive_speed: false,
+            trim_silence: None,
+            double_read_verify: false,
+            concealment: Concealment::Silence,
+        }
+    }
+}
+
+impl RipConfig {
+    /// Makes [`CdromParanoia::read_chunks`] and [`Ripper::rip_track`]
+    /// slow the drive down on repeated read errors, which often lets it
+    /// read sectors it otherwise cannot. [`TrackRip::adaptive_speed`] does
+    /// the same for a single track. Only reads through the paranoia engine
+    /// are governed; the direct backend of [`ParanoiaMode::DISABLE`]
+    /// reports no errors to go on.
+    ///
+    /// After 3 sectors in a row with `PARANOIA_CB_READERR` or
+    /// `PARANOIA_CB_BACKOFF` events, the speed drops one step, from the
+    /// drive's current speed (the one last set with
+    /// [`CdromDrive::set_speed`], else what the drive reports where it can
+    /// be asked, else its maximum) through whichever of 32x, 16x, 8x, 4x,
+    /// 2x and 1x are slower. After a run of clean sectors, at first 750 (10
+    /// seconds of audio), it goes back up one step, but never above the
+    /// starting speed. Whenever a slowdown follows a speed-up, the clean
+    /// run needed for later speed-ups doubles for the rest of the read, so
+    /// a marginal disc does not keep the speed bouncing. The drive is set
+    /// back to the starting speed when the read ends.
+    ///
+    /// Speed changes go through [`CdromDrive::set_speed`] and failures are
+    /// ignored. Some drives ignore speed changes while reading, or support
+    /// only some of the steps, in which case this has little effect.
+    pub fn adaptive_speed(mut self, enabled: bool) -> Self {
+        self.adaptive_speed = enabled;
+        self
+    }
+
+    /// Makes [`Ripper::rip_track`] remove leading and trailing frames whose
+    /// samples are all within `threshold` of zero, see
+    /// [`Samples::trim_silence`]. The number of samples removed is
+    /// available from [`Ripper::trimmed_samples`], e.g. for
+    /// [`TrackRipResult::trimmed_samples`](crate::TrackRipResult::trimmed_samples).
+    ///
+    /// The trimmed track no longer matches the disc, so it also no longer
+    /// matches AccurateRip or CRCs of untrimmed rips.
+    pub fn trim_silence(mut self, threshold: i16) -> Self {
+        self.trim_silence = Some(threshold);
+        self
+    }
+
+    /// Makes [`Ripper::rip_track`] read each track a second time and
+    /// compare the two reads, catching unreliable reads that paranoia
+    /// reported as successful. The sample ranges that differ are available
+    /// from [`Ripper::mismatches`], e.g. for
+    /// [`TrackRipResult::mismatches`](crate::TrackRipResult::mismatches).
+    ///
+    /// Between the passes, sectors elsewhere on the disc are read to push
+    /// the track out of the drive's cache (as many as
+    /// [`CdromParanoia::cache_size`] assumes it holds), so the second pass
+    /// comes from the disc again. This doubles the time taken to rip, plus
+    /// the seek away.
+    ///
+    /// The first read is returned; on a mismatch, neither read can be
+    /// trusted.
+    pub fn double_read_verify(mut self, enabled: bool) -> Self {
+        self.double_read_verify = enabled;
+        self
+    }
+
+    /// Makes [`Ripper::rip_track`] fill in the sectors paranoia gave up
+    /// verifying (`PARANOIA_CB_SKIP`, see [`CorrectionMap::is_skipped`])
+    /// with `concealment` instead of returning the unverified data the
+    /// engine read. The default, silence, keeps the damage obvious; the
+    /// others make it less audible.
+    ///
+    /// The direct backend of [`ParanoiaMode::DISABLE`] skips nothing, so
+    /// this does not apply to it.
+    pub fn concealment(mut self, concealment: Concealment) -> Self {
+        self.concealment = concealment;
+        self
+    }
+}
+
+/// Consecutive sectors with read errors or backoffs before slowing down.
+const SLOWDOWN_AFTER: u32 = 3;
+/// Consecutive clean sectors before speeding back up, at first.
+const SPEEDUP_AFTER: u32 = 750;
+/// Speeds stepped down through below the starting speed, fastest first.
+const SPEED_STEPS: [i32; 6] = [32, 16, 8, 4, 2, 1];
+
+/// The speed the drive reads at before a governed read: the one last set,
+/// else what the drive reports where it can be asked.
+fn starting_speed(drive: &CdromDrive) -> Option<i32> {
+    #[cfg(target_os = "linux")]
+    let reported = || drive.speed();
+    #[cfg(not(target_os = "linux"))]
+    let reported = || None;
+    drive.requested_speed().or_else(reported)
+}
+
+/// Picks drive speeds for [`RipConfig::adaptive_speed`].
+///
+/// Step 0 is the speed the drive was at when the governor started, and
+/// later steps are the [`SPEED_STEPS`] slower than that, so the drive is
+/// never sped up past where it started.
+#[derive(Debug, Clone, Copy)]
+struct SpeedGovernor {
+    /// The starting speed, -1 for the drive's maximum.
+    initial: i32,
+    step: usize,
+    troubled_run: u32,
+    clean_run: u32,
+    speedup_after: u32,
+    /// Whether the last change was a speed-up. Stays set until the next
+    /// slowdown.
+    sped_up: bool,
+}
+
+impl SpeedGovernor {
+    /// Starts at `initial`, as returned by [`starting_speed`]; `None` is
+    /// taken as the drive's maximum.
+    fn new(initial: Option<i32>) -> Self {
+        Self {
+            initial: initial.unwrap_or(-1),
+            step: 0,
+            troubled_run: 0,
+            clean_run: 0,
+            speedup_after: SPEEDUP_AFTER,
+            sped_up: false,
+        }
+    }
+
+    /// Records one read, returning the speed to switch to, if any.
+    fn record(&mut self, telemetry: &SectorTelemetry) -> Option<i32> {
+        if telemetry.read_errors > 0 || telemetry.backoffs > 0 {
+            self.clean_run = 0;
+            self.troubled_run += 1;
+            if self.troubled_run < SLOWDOWN_AFTER || self.step + 1 == self.steps() {
+                return None;
+            }
+            if self.sped_up {
+                self.speedup_after = self.speedup_after.saturating_mul(2);
+            }
+            self.step += 1;
+            self.sped_up = false;
+        } else {
+            self.troubled_run = 0;
+            self.clean_run += 1;
+            if self.clean_run < self.speedup_after || self.step == 0 {
+                return None;
+            }
+            self.step -= 1;
+            self.sped_up = true;
+        }
+        self.troubled_run = 0;
+        self.clean_run = 0;
+        Some(self.speed())
+    }
+
+    /// The speeds of the steps, starting speed first.
+    fn speeds(&self) -> impl Iterator<Item = i32> {
+        let initial = self.initial;
+        std::iter::once(initial).chain(
+            SPEED_STEPS
+                .into_iter()
+                .filter(move |&speed| initial <= 0 || speed < initial),
+        )
+    }
+
+    fn steps(&self) -> usize {
+        self.speeds().count()
+    }
+
+    /// The speed of the current step.
+    fn speed(&self) -> i32 {
+        self.speeds().nth(self.step).unwrap()
+    }
+
+    /// Puts the drive back at the starting speed if the governor changed
+    /// it.
+    fn restore(&self, drive: &CdromDrive) {
+        if self.step > 0 {
+            let _ = drive.set_speed(self.initial);
+        }
+    }
+}
+
+/// Iterator over chunks of a sector range, returned by
+/// [`CdromParanoia::read_chunks`].
+///
+/// Each item holds the samples of up to
+/// [`read_chunk_sectors`](RipConfig::read_chunk_sectors) sectors; only the
+/// last chunk can be shorter. Iteration stops after the first error.
+pub struct ReadChunks<'a> {
+    paranoia: &'a mut CdromParanoia,
+    callback: extern "C" fn(c_long, c_int),
+    next_sector: u64,
+    /// One past the last sector to read.
+    end_sector: u64,
+    chunk_sectors: u64,
+    governor: Option<SpeedGovernor>,
+}
+
+impl Iterator for ReadChunks<'_> {
+    type Item = Result<Vec<i16>, Error>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.next_sector >= self.end_sector {
+            return None;
+        }
+        let sectors = self.chunk_sectors.min(self.end_sector - self.next_sector);
+        let mut samples = Vec::with_capacity(sectors as usize * CD_FRAMEWORDS as usize);
+        for _ in 0..sectors {
+            match self.paranoia.read_checked(Some(self.callback)) {
+                Ok(sector) => samples.extend_from_slice(sector),
+                Err(error) => {
+                    self.next_sector = self.end_sector;
+                    return Some(Err(error));
+                }
+            }
+            if let Some(governor) = &mut self.governor {
+                let telemetry = self.paranoia.last_sector_telemetry();
+                if let Some(speed) = governor.record(&telemetry) {
+                    let _ = self.paranoia.drive().set_speed(speed);
+                }
+            }
+        }
+        self.next_sector += sectors;
+        Some(Ok(samples))
+    }
+}
+
+impl<'a> ReadChunks<'a> {
+    /// Re-chunks the samples into blocks of `block_size` samples, see
+    /// [`Frames`].
+    ///
+    /// # Panics
+    ///
+    /// If `block_size` is zero.
+    pub fn frames(self, block_size: usize) -> Frames<Self> {
+        Frames::new(self, block_size)
+    }
+}
+
+/// Groups a stream of sample chunks into blocks of a fixed number of
+/// samples, independent of the sector size, e.g. for an encoder's block
+/// size.
+///
+/// Samples left over at the end of a chunk are carried into the next
+/// block. The last block holds whatever remains and can be shorter; no
+/// block is empty. An error from the inner iterator is passed on in place
+/// of the block it interrupted, whose buffered samples are dropped.
+pub struct Frames<I> {
+    inner: I,
+    block_size: usize,
+    buffer: Vec<i16>,
+    /// Start of the samples in `buffer` not yet returned.
+    start: usize,
+}
+
+impl<I> Frames<I>
+where
+    I: Iterator<Item = Result<Vec<i16>, Error>>,
+{
+    /// # Panics
+    ///
+    /// If `block_size` is zero.
+    pub fn new(inner: I, block_size: usize) -> Self {
+        assert!(block_size > 0, "block size must be non-zero");
+        Self {
+            inner,
+            block_size,
+            buffer: Vec::new(),
+            start: 0,
+        }
+    }
+}
+
+impl<I> Iterator for Frames<I>
+where
+    I: Iterator<Item = Result<Vec<i16>, Error>>,
+{
+    type Item = Result<Vec<i16>, Error>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        while self.buffer.len() - self.start < self.block_size {
+            match self.inner.next() {
+                Some(Ok(chunk)) => {
+                    self.buffer.drain(..self.start);
+                    self.start = 0;
+                    self.buffer.extend_from_slice(&chunk);
+                }
+                Some(Err(error)) => {
+                    self.buffer.clear();
+                    self.start = 0;
+                    return Some(Err(error));
+                }
+                None => break,
+            }
+        }
+        let end = (self.start + self.block_size).min(self.buffer.len());
+        if end == self.start {
+            return None;
+        }
+        let block = self.buffer[self.start..end].to_vec();
+        self.start = end;
+        Some(Ok(block))
+    }
+}
+
+impl Drop for ReadChunks<'_> {
+    fn drop(&mut self) {
+        if let Some(governor) = &self.governor {
+            governor.restore(self.paranoia.drive());
+        }
+    }
+}
+
+impl CdromParanoia {
+    /// Reads the sectors in `range` (absolute, inclusive) in chunks of
+    /// [`read_chunk_sectors`](RipConfig::read_chunk_sectors).
+    ///
+    /// Like [`read_range`](Self::read_range), the range is checked to be
+    /// audio and seeked to up front, so those errors are returned here
+    /// rather than from the iterator.
+    pub fn read_chunks(
+        &mut self,
+        range: RangeInclusive<u64>,
+        config: &RipConfig,
+        callback: extern "C" fn(c_long, c_int),
+    ) -> Result<ReadChunks<'_>, Error> {
+        let (first_sector, last_sector) = range.into_inner();
+        if first_sector <= last_sector {
+            self.check_audio(first_sector, last_sector)?;
+            self.seek(SeekFrom::Start(first_sector))?;
+        }
+        let governor = config
+            .adaptive_speed
+            .then(|| SpeedGovernor::new(starting_speed(self.drive())));
+        Ok(ReadChunks {
+            paranoia: self,
+            callback,
+            next_sector: first_sector,
+            end_sector: last_sector.saturating_add(1).max(first_sector),
+            chunk_sectors: config.read_chunk_sectors.max(1).into(),
+            governor,
+        })
+    }
+}
+
+/// Reads a sector range into a caller-owned buffer, for rippers that must
+/// not allocate while ripping.
+///
+/// Each call to [`next_block`](Self::next_block) fills as many whole
+/// sectors as fit into the buffer and returns that part of it. The buffer
+/// must hold at least one sector, [`CD_FRAMEWORDS`] samples; a multiple of
+/// that wastes nothing. Neither construction nor reading allocates on the
+/// Rust side. The paranoia engine inside the library still manages its own
+/// cache, which it allocates as it reads.
+pub struct BufferedReader<'a> {
+    paranoia: &'a mut CdromParanoia,
+    buffer: &'a mut [i16],
+    callback: extern "C" fn(c_long, c_int),
+    next_sector: u64,
+    /// One past the last sector to read.
+    end_sector: u64,
+}
+
+impl<'a> BufferedReader<'a> {
+    /// Prepares to read the sectors in `range` (absolute, inclusive) into
+    /// `buffer`.
+    ///
+    /// Like [`CdromParanoia::read_range`], the range is checked to be audio
+    /// and seeked to up front.
+    ///
+    /// # Panics
+    ///
+    /// If `buffer` is shorter than one sector.
+    pub fn new(
+        paranoia: &'a mut CdromParanoia,
+        buffer: &'a mut [i16],
+        range: RangeInclusive<u64>,
+        callback: extern "C" fn(c_long, c_int),
+    ) -> Result<Self, Error> {
+        assert!(
+            buffer.len() >= CD_FRAMEWORDS as usize,
+            "buffer must hold at least one sector"
+        );
+        let (first_sector, last_sector) = range.into_inner();
+        if first_sector <= last_sector {
+            paranoia.check_audio(first_sector, last_sector)?;
+            paranoia.seek(SeekFrom::Start(first_sector))?;
+        }
+        Ok(Self {
+            paranoia,
+            buffer,
+            callback,
+            next_sector: first_sector,
+            end_sector: last_sector.saturating_add(1).max(first_sector),
+        })
+    }
+
+    /// Sectors not read yet.
+    pub fn remaining_sectors(&self) -> u64 {
+        self.end_sector - self.next_sector
+    }
+
+    /// Reads the next sectors into the buffer and returns the filled part,
+    /// or `None` at the end of the range.
+    ///
+    /// After an error, the sector that failed is read again by the next
+    /// call, following a seek back to it.
+    pub fn next_block(&mut self) -> Result<Option<&[i16]>, Error> {
+        let capacity = (self.buffer.len() / CD_FRAMEWORDS as usize) as u64;
+        let sectors = capacity.min(self.remaining_sectors()) as usize;
+        if sectors == 0 {
+            return Ok(None);
+        }
+        let chunks = self.buffer.chunks_exact_mut(CD_FRAMEWORDS as usize);
+        for (index, chunk) in chunks.take(sectors).enumerate() {
+            match self.paranoia.read_checked(Some(self.callback)) {
+                Ok(sector) => chunk.copy_from_slice(sector),
+                Err(error) => {
+                    self.next_sector += index as u64;
+                    // If this fails too, the next read reports it.
+                    let _ = self.paranoia.seek(SeekFrom::Start(self.next_sector));
+                    return Err(error);
+                }
+            }
+        }
+        self.next_sector += sectors as u64;
+        Ok(Some(&self.buffer[..sectors * CD_FRAMEWORDS as usize]))
+    }
+}
+
+/// Reads an audio track one verified sector at a time, returned by
+/// [`CdromParanoia::rip_track`].
+///
+/// Sectors are borrowed from the engine's buffer, so this is not an
+/// [`Iterator`]: each sector must be let go of before the next is read,
+/// which the borrow taken by [`next_sector`](Self::next_sector) enforces.
+/// Reading stops after the first error.
+pub struct TrackRip<'a> {
+    paranoia: &'a mut CdromParanoia,
+    callback: extern "C" fn(c_long, c_int),
+    next_sector: u64,
+    /// One past the last sector of the track.
+    end_sector: u64,
+    stats: RipStats,
+    governor: Option<SpeedGovernor>,
+    /// Speed the governor chose after the last read, applied before the
+    /// next one, since the sector returned still borrows the session.
+    pending_speed: Option<i32>,
+}
+
+impl TrackRip<'_> {
+    /// Slows the drive down while it struggles, see
+    /// [`RipConfig::adaptive_speed`].
+    pub fn adaptive_speed(mut self, enabled: bool) -> Self {
+        self.governor = enabled.then(|| SpeedGovernor::new(starting_speed(self.paranoia.drive())));
+        self
+    }
+
+    /// Sectors not read yet.
+    pub fn remaining_sectors(&self) -> u64 {
+        self.end_sector - self.next_sector
+    }
+
+    /// Statistics over the sectors read so far, e.g. to judge the rip
+    /// once every sector has been read.
+    pub fn stats(&self) -> &RipStats {
+        &self.stats
+    }
+
+    /// Reads the next sector of the track, or returns `None` once every
+    /// sector has been read.
+    pub fn next_sector(&mut self) -> Option<Result<&[i16; CD_FRAMEWORDS as usize], Error>> {
+        if self.next_sector >= self.end_sector {
+            return None;
+        }
+        if let Some(speed) = self.pending_speed.take() {
+            let _ = self.paranoia.drive().set_speed(speed);
+        }
+        let (stats, callback) = (&mut self.stats, self.callback);
+        let mut telemetry = SectorTelemetry::default();
+        let result = self.paranoia.read_checked_with(&mut |position, event| {
+            telemetry.record(position, event);
+            stats.record(ParanoiaEvent::from_raw(event));
+            callback(position, event);
+        });
+        if let Some(governor) = &mut self.governor {
+            self.pending_speed = governor.record(&telemetry);
+        }
+        match result {
+            Ok(sector) => {
+                self.stats.end_sector(&telemetry);
+                self.next_sector += 1;
+                Some(Ok(sector))
+            }
+            Err(error) => {
+                self.next_sector = self.end_sector;
+                Some(Err(error))
+            }
+        }
+    }
+}
+
+impl TrackRip<'_> {
+    /// Reads the remaining sectors into one buffer.
+    fn read_to_end(&mut self) -> Result<Vec<i16>, Error> {
+        let mut samples =
+            Vec::with_capacity(self.remaining_sectors() as usize * CD_FRAMEWORDS as usize);
+        while let Some(sector) = self.next_sector() {
+            samples.extend_from_slice(sector?);
+        }
+        Ok(samples)
+    }
+}
+
+impl Drop for TrackRip<'_> {
+    fn drop(&mut self) {
+        if let Some(governor) = &self.governor {
+            governor.restore(self.paranoia.drive());
+        }
+    }
+}
+
+impl CdromParanoia {
+    /// Seeks to the start of an audio track and returns a reader for
+    /// exactly its sectors.
+    ///
+    /// Returns [`ErrorCode::TrackNotAudio`] for data tracks and
+    /// [`ErrorCode::IllegalToc`] if the TOC gives the track no sectors,
+    /// before seeking.
+    pub fn rip_track(
+        &mut self,
+        track: u32,
+        callback: extern "C" fn(c_long, c_int),
+    ) -> Result<TrackRip<'_>, Error> {
+        let range = self.drive().track_sector_range(track)?;
+        if !self.drive().track_audiop(track)? {
+            return Err(Error::from_code(ErrorCode::TrackNotAudio));
+        }
+        self.seek(SeekFrom::Start(*range.start()))?;
+        Ok(TrackRip {
+            paranoia: self,
+            callback,
+            next_sector: *range.start(),
+            end_sector: *range.end() + 1,
+            stats: RipStats::default(),
+            governor: None,
+            pending_speed: None,
+        })
+    }
+
+    /// Like [`read_track`](Self::read_track), also collecting statistics
+    /// over the track's sectors, see [`TrackRip::stats`].
+    pub fn read_track_with_stats(
+        &mut self,
+        track: u32,
+        callback: extern "C" fn(c_long, c_int),
+    ) -> Result<(Samples, RipStats), Error> {
+        let mut rip = self.rip_track(track, callback)?;
+        let samples = rip.read_to_end()?;
+        Ok((Samples::cd(samples), std::mem::take(&mut rip.stats)))
+    }
+}
+
+/// Picks the mode of each track, see [`Ripper::set_mode_for_track`].
+type ModeForTrack = Box<dyn Fn(u32) -> ParanoiaMode + Send>;
+
+/// Rips tracks in a fixed [`ParanoiaMode`], or one chosen per track,
+/// choosing the read backend to suit it.
+///
+/// With [`ParanoiaMode::DISABLE`] there is nothing for paranoia to check,
+/// so tracks are read straight from the drive with `cdda_read`, many
+/// sectors per request. Any other mode reads through the paranoia engine,
+/// like [`CdromParanoia::read_track`].
+pub struct Ripper {
+    paranoia: CdromParanoia,
+    mode: ParanoiaMode,
+    mode_for_track: Option<ModeForTrack>,
+    config: RipConfig,
+    trimmed_samples: usize,
+    mismatches: Option<Vec<Range<u64>>>,
+}
+
+impl Ripper {
+    /// Sets `mode` on the session (see [`CdromParanoia::set_mode`]) and
+    /// wraps it.
+    pub fn new(paranoia: CdromParanoia, mode: ParanoiaMode) -> Result<Self, Error> {
+        let mode = paranoia.set_mode(mode)?;
+        Ok(Self {
+            paranoia,
+            mode,
+            mode_for_track: None,
+            config: RipConfig::default(),
+            trimmed_samples: 0,
+            mismatches: None,
+        })
+    }
+
+    /// Sets the configuration applied to every track ripped. Of its
+    /// settings, [`adaptive_speed`](RipConfig::adaptive_speed),
+    /// [`trim_silence`](RipConfig::trim_silence),
+    /// [`double_read_verify`](RipConfig::double_read_verify) and
+    /// [`concealment`](RipConfig::concealment) apply here.
+    pub fn set_config(&mut self, config: RipConfig) {
+        self.config = config;
+    }
+
+    /// Samples [`RipConfig::trim_silence`] removed from the last track
+    /// ripped.
+    pub fn trimmed_samples(&self) -> usize {
+        self.trimmed_samples
+    }
+
+    /// Sample ranges, from the start of the untrimmed track, in which the
+    /// two reads of the last track ripped differed, see
+    /// [`RipConfig::double_read_verify`]. Empty if they matched; `None` if
+    /// the track was read only once.
+    pub fn mismatches(&self) -> Option<&[Range<u64>]> {
+        self.mismatches.as_deref()
+    }
+
+    /// The mode actually applied, after normalization. With
+    /// [`set_mode_for_track`](Self::set_mode_for_track), the mode of the
+    /// last track ripped.
+    pub fn mode(&self) -> ParanoiaMode {
+        self.mode
+    }
+
+    /// Makes [`rip_track`](Self::rip_track) apply the mode returned for
+    /// each track number before reading the track, e.g. to escalate to
+    /// [`ParanoiaMode::FULL`] only on tracks known to be damaged while
+    /// clean ones rip faster. Replaces the fixed mode given to
+    /// [`new`](Self::new).
+    ///
+    /// The engine picks up a mode change mid-disc only on its next read,
+    /// and still holds data verified under the old mode until it is
+    /// flushed. Each track starts with a seek, which discards that data, so
+    /// no track is read in a mix of modes.
+    pub fn set_mode_for_track(
+        &mut self,
+        mode_for_track: impl Fn(u32) -> ParanoiaMode + Send + 'static,
+    ) {
+        self.mode_for_track = Some(Box::new(mode_for_track));
+    }
+
+    /// Whether tracks are read through the paranoia engine rather than
+    /// directly from the drive.
+    pub fn uses_paranoia(&self) -> bool {
+        self.mode != ParanoiaMode::DISABLE
+    }
+
+    pub fn paranoia(&self) -> &CdromParanoia {
+        &self.paranoia
+    }
+
+    pub fn into_inner(self) -> CdromParanoia {
+        self.paranoia
+    }
+
+    /// Reads an audio track.
+    ///
+    /// `callback` receives the paranoia engine's events; the direct backend
+    /// has none to report, so it is not called in
+    /// [`ParanoiaMode::DISABLE`].
+    ///
+    /// Returns [`ErrorCode::TrackNotAudio`] for data tracks.
+    pub fn rip_track(
+        &mut self,
+        track: u32,
+        callback: extern "C" fn(c_long, c_int),
+    ) -> Result<Samples, Error> {
+        if let Some(mode_for_track) = &self.mode_for_track {
+            self.mode = self.paranoia.set_mode(mode_for_track(track))?;
+        }
+        self.trimmed_samples = 0;
+        self.mismatches = None;
+        let mut samples = self.read_once(track, callback)?;
+        if self.config.double_read_verify {
+            self.defeat_cache(track)?;
+            let second = self.read_once(track, callback)?;
+            self.mismatches = Some(mismatched_ranges(&samples, &second));
+        }
+        if let Some(threshold) = self.config.trim_silence {
+            self.trimmed_samples = samples.trim_silence(threshold);
+        }
+        Ok(samples)
+    }
+
+    fn read_once(
+        &mut self,
+        track: u32,
+        callback: extern "C" fn(c_long, c_int),
+    ) -> Result<Samples, Error> {
+        if self.uses_paranoia() {
+            let range = self.paranoia.drive().track_sector_range(track)?;
+            self.paranoia.corrections = Some(CorrectionMap::new(range));
+            let adaptive_speed = self.config.adaptive_speed;
+            let result = self
+                .paranoia
+                .rip_track(track, callback)
+                .and_then(|rip| rip.adaptive_speed(adaptive_speed).read_to_end());
+            let corrections = self.paranoia.corrections.take().unwrap();
+            let mut samples = result?;
+            corrections.conceal(&mut samples, self.config.concealment);
+            Ok(Samples::cd(samples))
+        } else {
+            self.paranoia.ensure_open()?;
+            let range = self.paranoia.drive().track_sector_range(track)?;
+            Ok(Samples::cd(read_direct(self.paranoia.drive(), range)?))
+        }
+    }
+
+    /// Reads audio sectors away from `track`, before it if there is room
+    /// and after it otherwise, until the drive's cache no longer holds any
+    /// of it.
+    fn defeat_cache(&self, track: u32) -> Result<(), Error> {
+        let drive = self.paranoia.drive();
+        let track_range = drive.track_sector_range(track)?;
+        let count = u64::from(self.paranoia.cache_size().max(1));
+        let first = drive.disc_first_sector()?;
+        let last = drive.disc_last_sector()?;
+        let start = if track_range.start().saturating_sub(first) >= count {
+            first
+        } else if last.saturating_sub(*track_range.end()) >= count {
+            last + 1 - count
+        } else {
+            // Too little of the disc lies outside the track to fill the
+            // cache with.
+            return Ok(());
+        };
+        // Only the seeking matters; sectors that fail to read still move
+        // the head.
+        let _ = read_direct(drive, start..=start + count - 1);
+        Ok(())
+    }
+}
+
+/// Ranges of sample indices at which `first` and `second` differ, merging
+/// adjacent differences. Samples past the end of the shorter one count as
+/// differing.
+fn mismatched_ranges(first: &[i16], second: &[i16]) -> Vec<Range<u64>> {
+    let len = first.len().max(second.len());
+    let mut ranges: Vec<Range<u64>> = Vec::new();
+    for index in 0..len {
+        if first.get(index) == second.get(index) {
+            continue;
+        }
+        let index = index as u64;
+        match ranges.last_mut() {
+            Some(range) if range.end == index => range.end = index + 1,
+            _ => ranges.push(index..index + 1),
+        }
+    }
+    ranges
+}
+
+/// Reads `range` with [`CdromDrive::read_sectors`], as many sectors per request as the
+/// drive allows.
+fn read_direct(drive: &CdromDrive, range: RangeInclusive<u64>) -> Result<Vec<i16>, Error> {
+    let (first_sector, last_sector) = range.into_inner();
+    let total = last_sector.saturating_add(1).saturating_sub(first_sector);
+    let per_request = unsafe { (*drive.as_raw()).nsectors }.max(1) as u64;
+    let mut samples = vec![0i16; total as usize * CD_FRAMEWORDS as usize];
+
+    let mut done = 0;
+    while done < total {
+        let sectors = per_request.min(total - done) as u32;
+        let buffer = &mut samples[done as usize * CD_FRAMEWORDS as usize..];
+        let read = drive.read_sectors(first_sector + done, sectors, buffer)?;
+        if read == 0 {
+            return Err(Error::from_code(ErrorCode::UnknownReadError));
+        }
+        done += u64::from(read);
+    }
+    Ok(samples)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn troubled() -> SectorTelemetry {
+        SectorTelemetry {
+            read_errors: 1,
+            ..Default::default()
+        }
+    }
+
+    fn record_n(governor: &mut SpeedGovernor, telemetry: SectorTelemetry, n: u32) -> Vec<i32> {
+        (0..n).filter_map(|_| governor.record(&telemetry)).collect()
+    }
+
+    fn sectors(count: i16) -> impl Iterator<Item = Result<Vec<i16>, Error>> {
+        (0..count).map(|sector| Ok(vec![sector; CD_FRAMEWORDS as usize]))
+    }
+
+    #[test]
+    fn finds_mismatched_ranges() {
+        let first = [0, 1, 2, 3, 4, 5, 6];
+        assert_eq!(mismatched_ranges(&first, &first), []);
+        let second = [0, 9, 9, 3, 4, 9, 6, 7];
+        assert_eq!(mismatched_ranges(&first, &second), [1..3, 5..6, 7..8]);
+    }
+
+    #[test]
+    fn frames_span_sectors() {
+        // 3 sectors = 3528 samples = 3 blocks of 1000 and one of 528.
+        let blocks: Vec<_> = Frames::new(sectors(3), 1000)
+            .collect::<Result<_, _>>()
+            .unwrap();
+        let lengths: Vec<_> = blocks.iter().map(Vec::len).collect();
+        assert_eq!(lengths, [1000, 1000, 1000, 528]);
+        assert_eq!(blocks[1][175], 0);
+        assert_eq!(blocks[1][176], 1);
+        assert_eq!(
+            blocks.concat(),
+            sectors(3).flatten().flatten().collect::<Vec<_>>()
+        );
+
+        // Blocks larger than a sector, and a sector size that divides evenly.
+        let lengths: Vec<_> = Frames::new(sectors(5), 4096)
+            .map(|block| block.unwrap().len())
+            .collect();
+        assert_eq!(lengths, [4096, 1784]);
+        assert_eq!(Frames::new(sectors(2), 588).count(), 4);
+        assert_eq!(Frames::new(sectors(0), 588).count(), 0);
+    }
+
+    #[test]
+    fn frames_pass_errors_on() {
+        let chunks = sectors(1).chain([Err(Error::from_code(ErrorCode::UnknownReadError))]);
+        let mut frames = Frames::new(chunks, 1000);
+        assert_eq!(frames.next().unwrap().unwrap().len(), 1000);
+        assert!(frames.next().unwrap().is_err());
+        assert!(frames.next().is_none());
+    }
+
+    #[test]
+    fn adaptive_speed_steps_down_and_up() {
+        let clean = SectorTelemetry::default();
+        let mut governor = SpeedGovernor::new(None);
+        assert_eq!(record_n(&mut governor, clean, 1000), []);
+        // Isolated errors do not slow down.
+        assert_eq!(record_n(&mut governor, troubled(), 2), []);
+        assert_eq!(record_n(&mut governor, clean, 1), []);
+        assert_eq!(record_n(&mut governor, troubled(), 6), [32, 16]);
+        assert_eq!(record_n(&mut governor, clean, 749), []);
+        assert_eq!(record_n(&mut governor, clean, 751), [32, -1]);
+        assert_eq!(record_n(&mut governor, clean, 1000), []);
+    }
+
+    #[test]
+    fn adaptive_speed_does_not_thrash() {
+        let clean = SectorTelemetry::default();
+        let mut governor = SpeedGovernor::new(None);
+        assert_eq!(record_n(&mut governor, troubled(), 6), [32, 16]);
+        assert_eq!(record_n(&mut governor, clean, 750), [32]);
+        // Trouble right after speeding up doubles the clean run needed.
+        assert_eq!(record_n(&mut governor, troubled(), 3), [16]);
+        assert_eq!(record_n(&mut governor, clean, 1499), []);
+        assert_eq!(record_n(&mut governor, clean, 1), [32]);
+    }
+
+    #[test]
+    fn adaptive_speed_bottoms_out() {
+        let mut governor = SpeedGovernor::new(None);
+        let speeds = record_n(&mut governor, troubled(), 100);
+        assert_eq!(speeds, SPEED_STEPS);
+    }
+
+    #[test]
+    fn adaptive_speed_stays_below_starting_speed() {
+        let clean = SectorTelemetry::default();
+        let mut governor = SpeedGovernor::new(Some(4));
+        assert_eq!(record_n(&mut governor, troubled(), 100), [2, 1]);
+        assert_eq!(record_n(&mut governor, clean, 10000), [2, 4]);
+        assert_eq!(record_n(&mut governor, clean, 10000), []);
+    }
+
+    #[test]
+    fn adaptive_speed_restores_starting_speed_on_drop() {
+        extern "C" fn ignore_event(_: c_long, _: c_int) {}
+
+        let mut fake = crate::testing::FakeParanoia::new(&[(0, 0)], 100);
+        let paranoia = fake.paranoia();
+        paranoia.drive().set_speed(4).unwrap();
+        let mut rip = paranoia
+            .rip_track(1, ignore_event)
+            .unwrap()
+            .adaptive_speed(true);
+        let governor = rip.governor.as_mut().unwrap();
+        for speed in record_n(governor, troubled(), 6) {
+            rip.paranoia.drive().set_speed(speed).unwrap();
+        }
+        assert_eq!(rip.paranoia.drive().requested_speed(), Some(1));
+        drop(rip);
+        assert_eq!(paranoia.drive().requested_speed(), Some(4));
+    }
+}
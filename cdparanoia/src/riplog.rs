@@ -0,0 +1,227 @@
+//! Text logs describing a finished rip.
+
+use std::{
+    fmt::{self, Write},
+    ops::{Range, RangeInclusive},
+};
+
+use crate::{format::Msf, AccurateRipVersion, DiscLayout, ParanoiaMode, VerifyResult};
+
+/// What ripping one track produced.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TrackRipResult {
+    pub number: u32,
+    pub sectors: RangeInclusive<u64>,
+    /// CRC-32 of the track's samples, see [`crate::Crc32`].
+    pub crc32: u32,
+    /// AccurateRip outcome, if the track was checked.
+    pub accuraterip: Option<VerifyResult>,
+    /// Sectors whose read needed retries, see
+    /// [`SectorTelemetry::is_troubled`](crate::SectorTelemetry::is_troubled).
+    pub troubled_sectors: u64,
+    /// Sectors the engine gave up verifying and skipped.
+    pub skipped_sectors: u64,
+    /// Samples removed as edge silence, see
+    /// [`RipConfig::trim_silence`](crate::RipConfig::trim_silence). 0 for
+    /// exact rips.
+    pub trimmed_samples: u64,
+    /// Sample ranges in which two reads of the track differed, see
+    /// [`RipConfig::double_read_verify`](crate::RipConfig::double_read_verify).
+    /// `None` if it was read once.
+    pub mismatches: Option<Vec<Range<u64>>>,
+}
+
+/// Accumulates the details of a rip and formats them as a log.
+///
+/// The log is line-based `Key: value` text with one indented block per
+/// track, in the order the tracks were added. Its layout is stable, so
+/// logs can be parsed back as well as read.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct RipLog {
+    drive: Option<String>,
+    read_offset: i32,
+    mode: ParanoiaMode,
+    first_track: Option<Msf>,
+    lead_out: Option<Msf>,
+    tracks: Vec<TrackRipResult>,
+}
+
+impl RipLog {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Sets the drive description, e.g. its model string.
+    pub fn drive(mut self, drive: impl Into<String>) -> Self {
+        self.drive = Some(drive.into());
+        self
+    }
+
+    /// Sets the read offset correction, in samples, that was applied.
+    pub fn read_offset(mut self, read_offset: i32) -> Self {
+        self.read_offset = read_offset;
+        self
+    }
+
+    /// Sets the paranoia mode used for the rip.
+    pub fn mode(mut self, mode: ParanoiaMode) -> Self {
+        self.mode = mode;
+        self
+    }
+
+    /// Records the addresses of the disc's first track and lead-out, see
+    /// [`DiscLayout::first_track_msf`] and [`DiscLayout::lead_out_msf`].
+    pub fn disc(mut self, layout: &DiscLayout) -> Self {
+        self.first_track = layout.first_track_msf();
+        self.lead_out = layout.lead_out_msf();
+        self
+    }
+
+    /// Adds the result of one track.
+    pub fn track(mut self, result: TrackRipResult) -> Self {
+        self.tracks.push(result);
+        self
+    }
+
+    pub fn tracks(&self) -> &[TrackRipResult] {
+        &self.tracks
+    }
+}
+
+impl fmt::Display for RipLog {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "Drive: {}", self.drive.as_deref().unwrap_or("unknown"))?;
+        writeln!(f, "Read offset: {:+}", self.read_offset)?;
+        writeln!(f, "Paranoia mode: {:#04x}", self.mode.bits())?;
+        writeln!(f, "First track: {}", OptionalMsf(self.first_track))?;
+        writeln!(f, "Lead-out: {}", OptionalMsf(self.lead_out))?;
+        writeln!(f, "Tracks: {}", self.tracks.len())?;
+
+        for track in &self.tracks {
+            let mut accuraterip = String::new();
+            match track.accuraterip {
+                None => accuraterip.push_str("not checked"),
+                Some(VerifyResult::NoMatch) => accuraterip.push_str("no match"),
+                Some(VerifyResult::Matched(version)) => {
+                    let version = match version {
+                        AccurateRipVersion::V1 => 1,
+                        AccurateRipVersion::V2 => 2,
+                    };
+                    write!(accuraterip, "matched (v{version})")?;
+                }
+            }
+
+            writeln!(f)?;
+            writeln!(f, "Track {:02}", track.number)?;
+            writeln!(
+                f,
+                "  Sectors: {}-{}",
+                track.sectors.start(),
+                track.sectors.end()
+            )?;
+            writeln!(f, "  CRC32: {:08X}", track.crc32)?;
+            writeln!(f, "  AccurateRip: {accuraterip}")?;
+            writeln!(f, "  Troubled sectors: {}", track.troubled_sectors)?;
+            writeln!(f, "  Skipped sectors: {}", track.skipped_sectors)?;
+            writeln!(f, "  Trimmed samples: {}", track.trimmed_samples)?;
+            write!(f, "  Re-read mismatches: ")?;
+            match &track.mismatches {
+                None => f.write_str("not checked")?,
+                Some(ranges) if ranges.is_empty() => f.write_str("none")?,
+                Some(ranges) => {
+                    for (i, range) in ranges.iter().enumerate() {
+                        let separator = if i == 0 { "" } else { ", " };
+                        write!(f, "{separator}{}-{}", range.start, range.end - 1)?;
+                    }
+                }
+            }
+            writeln!(f)?;
+        }
+        Ok(())
+    }
+}
+
+/// Formats an MSF address, or `unknown`.
+struct OptionalMsf(Option<Msf>);
+
+impl fmt::Display for OptionalMsf {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.0 {
+            Some(msf) => msf.fmt(f),
+            None => f.write_str("unknown"),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn formats_sample_rip() {
+        let log = RipLog::new()
+            .drive("PLEXTOR DVDR PX-716A")
+            .read_offset(30)
+            .mode(ParanoiaMode::FULL)
+            .disc(&DiscLayout {
+                tracks: vec![crate::Track {
+                    number: 1,
+                    first_sector: 0,
+                    last_sector: 29999,
+                    channels: 2,
+                    audio: true,
+                    copy_permitted: false,
+                    preemphasis: false,
+                }],
+                lead_out: 30000,
+            })
+            .track(TrackRipResult {
+                number: 1,
+                sectors: 0..=14999,
+                crc32: 0x1a2b3c,
+                accuraterip: Some(VerifyResult::Matched(AccurateRipVersion::V2)),
+                troubled_sectors: 0,
+                skipped_sectors: 0,
+                trimmed_samples: 0,
+                mismatches: Some(Vec::new()),
+            })
+            .track(TrackRipResult {
+                number: 2,
+                sectors: 15000..=29999,
+                crc32: 0xdeadbeef,
+                accuraterip: None,
+                troubled_sectors: 12,
+                skipped_sectors: 1,
+                trimmed_samples: 588,
+                mismatches: Some(vec![1176..1180, 2000..2001]),
+            });
+
+        assert_eq!(
+            log.to_string(),
+            "Drive: PLEXTOR DVDR PX-716A\n\
+             Read offset: +30\n\
+             Paranoia mode: 0xff\n\
+             First track: 00:02.00\n\
+             Lead-out: 06:42.00\n\
+             Tracks: 2\n\
+             \n\
+             Track 01\n  \
+               Sectors: 0-14999\n  \
+               CRC32: 001A2B3C\n  \
+               AccurateRip: matched (v2)\n  \
+               Troubled sectors: 0\n  \
+               Skipped sectors: 0\n  \
+               Trimmed samples: 0\n  \
+               Re-read mismatches: none\n\
+             \n\
+             Track 02\n  \
+               Sectors: 15000-29999\n  \
+               CRC32: DEADBEEF\n  \
+               AccurateRip: not checked\n  \
+               Troubled sectors: 12\n  \
+               Skipped sectors: 1\n  \
+               Trimmed samples: 588\n  \
+               Re-read mismatches: 1176-1179, 2000-2000\n"
+        );
+    }
+}
@@ -0,0 +1,346 @@
+//! PCM buffers that carry their format.
+
+#[cfg(feature = "wav")]
+use std::{io, path::Path};
+use std::{ops::Deref, time::Duration};
+
+use crate::{CD_FRAMESAMPLES, CD_FRAMESIZE_RAW, CD_FRAMEWORDS};
+
+/// Sample rate of CD audio.
+const CD_SAMPLE_RATE: u32 = 44100;
+
+/// Interleaved 16-bit PCM together with its channel count and sample rate.
+///
+/// Dereferences to the samples, so it can be used wherever a `&[i16]` is
+/// expected.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Samples {
+    data: Vec<i16>,
+    channels: u16,
+    sample_rate: u32,
+}
+
+impl Samples {
+    /// Wraps interleaved samples.
+    ///
+    /// # Panics
+    ///
+    /// If `channels` or `sample_rate` is zero, or `data` does not hold a
+    /// whole number of frames.
+    pub fn new(data: Vec<i16>, channels: u16, sample_rate: u32) -> Self {
+        assert!(channels > 0, "channel count must be non-zero");
+        assert!(sample_rate > 0, "sample rate must be non-zero");
+        assert!(
+            data.len() % usize::from(channels) == 0,
+            "samples must be whole frames"
+        );
+        Self {
+            data,
+            channels,
+            sample_rate,
+        }
+    }
+
+    /// Wraps stereo 44100 Hz samples as read from a disc.
+    pub(crate) fn cd(data: Vec<i16>) -> Self {
+        Self::new(data, 2, CD_SAMPLE_RATE)
+    }
+
+    pub fn channels(&self) -> u16 {
+        self.channels
+    }
+
+    pub fn sample_rate(&self) -> u32 {
+        self.sample_rate
+    }
+
+    /// Number of frames, i.e. samples per channel.
+    pub fn frames(&self) -> usize {
+        self.data.len() / usize::from(self.channels)
+    }
+
+    /// Playing time of the samples.
+    pub fn duration(&self) -> Duration {
+        let frames = self.frames() as u64;
+        let rate = u64::from(self.sample_rate);
+        Duration::from_secs(frames / rate)
+            + Duration::from_nanos(frames % rate * 1_000_000_000 / rate)
+    }
+
+    /// Removes the runs of near-silent frames at the start and end, where
+    /// every sample's absolute value is at most `threshold`, and returns
+    /// the number of samples removed. Samples that are entirely silent
+    /// become empty.
+    ///
+    /// This changes the audio, so it is for playback copies, not archival
+    /// rips.
+    pub fn trim_silence(&mut self, threshold: i16) -> usize {
+        let channels = usize::from(self.channels);
+        let silent = |frame: &[i16]| {
+            frame
+                .iter()
+                .all(|sample| i32::from(sample.unsigned_abs()) <= i32::from(threshold))
+        };
+        let frames = || self.data.chunks_exact(channels);
+        let leading = frames().take_while(|frame| silent(frame)).count();
+        let trailing = frames()
+            .skip(leading)
+            .rev()
+            .take_while(|frame| silent(frame))
+            .count();
+
+        let before = self.data.len();
+        self.data.truncate(before - trailing * channels);
+        self.data.drain(..leading * channels);
+        before - self.data.len()
+    }
+
+    /// Splits stereo samples into their left and right channels, or
+    /// returns `None` if there are not exactly two channels.
+    pub fn split_stereo(&self) -> Option<(Vec<i16>, Vec<i16>)> {
+        if self.channels != 2 {
+            return None;
+        }
+        let frames = self.data.chunks_exact(2);
+        Some(frames.map(|frame| (frame[0], frame[1])).unzip())
+    }
+
+    pub fn as_slice(&self) -> &[i16] {
+        &self.data
+    }
+
+    pub fn into_vec(self) -> Vec<i16> {
+        self.data
+    }
+
+    /// Views the samples as bytes in the host's byte order, without
+    /// copying. See [`sector_host_bytes`] for the caveat.
+    #[cfg(feature = "bytemuck")]
+    pub fn as_host_bytes(&self) -> &[u8] {
+        bytemuck::cast_slice(&self.data)
+    }
+}
+
+impl Deref for Samples {
+    type Target = [i16];
+
+    fn deref(&self) -> &Self::Target {
+        &self.data
+    }
+}
+
+#[cfg(feature = "wav")]
+impl Samples {
+    /// Writes the samples to a 16-bit PCM WAV file at `path`, with their
+    /// channel count and sample rate, replacing any existing file.
+    ///
+    /// WAV is little-endian whatever the host; the encoder takes care of
+    /// the conversion.
+    pub fn write_wav(&self, path: &Path) -> io::Result<()> {
+        let spec = hound::WavSpec {
+            channels: self.channels,
+            sample_rate: self.sample_rate,
+            bits_per_sample: 16,
+            sample_format: hound::SampleFormat::Int,
+        };
+        let mut writer = hound::WavWriter::create(path, spec).map_err(wav_error)?;
+        for &sample in &self.data {
+            writer.write_sample(sample).map_err(wav_error)?;
+        }
+        writer.finalize().map_err(wav_error)
+    }
+}
+
+/// Unwraps the I/O errors inside encoder errors.
+#[cfg(feature = "wav")]
+fn wav_error(error: hound::Error) -> io::Error {
+    match error {
+        hound::Error::IoError(error) => error,
+        error => io::Error::other(error),
+    }
+}
+
+/// Byte order of 16-bit PCM byte streams, independent of the host and
+/// drive byte order.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Endianness {
+    /// As used by WAV.
+    #[default]
+    Little,
+    /// As used by AIFF.
+    Big,
+}
+
+impl Endianness {
+    pub fn sample_bytes(self, sample: i16) -> [u8; 2] {
+        match self {
+            Self::Little => sample.to_le_bytes(),
+            Self::Big => sample.to_be_bytes(),
+        }
+    }
+
+    /// Rearranges a host-order sample so that its bytes in memory are in
+    /// this order, e.g. to write samples out as raw memory. Does nothing if
+    /// this is the host's order.
+    pub fn arrange(self, sample: i16) -> i16 {
+        match self {
+            Self::Little => sample.to_le(),
+            Self::Big => sample.to_be(),
+        }
+    }
+}
+
+/// Encodes the samples of one sector as bytes in the given order.
+pub fn sector_bytes(
+    sector: &[i16; CD_FRAMEWORDS as usize],
+    endianness: Endianness,
+) -> [u8; CD_FRAMESIZE_RAW as usize] {
+    let mut bytes = [0; CD_FRAMESIZE_RAW as usize];
+    for (bytes, &sample) in bytes.chunks_exact_mut(2).zip(sector) {
+        bytes.copy_from_slice(&endianness.sample_bytes(sample));
+    }
+    bytes
+}
+
+/// Splits the interleaved samples of one sector into its left and right
+/// channels.
+///
+/// This assumes a stereo track, as nearly all are. A track whose
+/// [`track_channels`](crate::CdromDrive::track_channels) is 4 interleaves
+/// four channels instead and must not be split this way; check the track,
+/// or use [`Samples::split_stereo`], which refuses anything but stereo.
+pub fn deinterleave(
+    sector: &[i16; CD_FRAMEWORDS as usize],
+    left: &mut [i16; CD_FRAMESAMPLES as usize],
+    right: &mut [i16; CD_FRAMESAMPLES as usize],
+) {
+    for ((frame, left), right) in sector.chunks_exact(2).zip(left).zip(right) {
+        *left = frame[0];
+        *right = frame[1];
+    }
+}
+
+/// Views the samples of one sector as bytes in the host's byte order,
+/// without copying.
+///
+/// Unlike [`sector_bytes`] the result depends on the host: it is only
+/// little-endian, as WAV wants, on little-endian hosts.
+#[cfg(feature = "bytemuck")]
+pub fn sector_host_bytes(
+    sector: &[i16; CD_FRAMEWORDS as usize],
+) -> &[u8; CD_FRAMESIZE_RAW as usize] {
+    bytemuck::cast_ref(sector)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn frames_and_duration() {
+        let samples = Samples::cd(vec![0; 2 * 66150]);
+        assert_eq!(samples.frames(), 66150);
+        assert_eq!(samples.duration(), Duration::from_millis(1500));
+        assert_eq!(samples.len(), 2 * 66150);
+    }
+
+    #[test]
+    fn sector_bytes_in_both_orders() {
+        let mut sector = [0; CD_FRAMEWORDS as usize];
+        sector[..3].copy_from_slice(&[0x0102, -2, 0x7f00]);
+        let le = sector_bytes(&sector, Endianness::Little);
+        assert_eq!(le[..6], [0x02, 0x01, 0xfe, 0xff, 0x00, 0x7f]);
+        let be = sector_bytes(&sector, Endianness::Big);
+        assert_eq!(be[..6], [0x01, 0x02, 0xff, 0xfe, 0x7f, 0x00]);
+        assert!(le[6..].iter().chain(&be[6..]).all(|&b| b == 0));
+        assert_eq!(sector_bytes(&sector, Endianness::default()), le);
+    }
+
+    #[test]
+    fn splits_channels() {
+        let sector: [i16; CD_FRAMEWORDS as usize] = std::array::from_fn(|i| {
+            let frame = (i / 2) as i16;
+            if i % 2 == 0 {
+                frame
+            } else {
+                -frame
+            }
+        });
+        let mut left = [0; CD_FRAMESAMPLES as usize];
+        let mut right = [0; CD_FRAMESAMPLES as usize];
+        deinterleave(&sector, &mut left, &mut right);
+        assert!((0..CD_FRAMESAMPLES as usize).all(|i| left[i] == i as i16));
+        assert!((0..CD_FRAMESAMPLES as usize).all(|i| right[i] == -(i as i16)));
+
+        let samples = Samples::cd(sector.to_vec());
+        assert_eq!(
+            samples.split_stereo(),
+            Some((left.to_vec(), right.to_vec()))
+        );
+        assert_eq!(Samples::new(vec![0; 8], 4, 44100).split_stereo(), None);
+    }
+
+    #[test]
+    fn rearranges_host_samples() {
+        let sample: i16 = 0x0102;
+        let little = Endianness::Little.arrange(sample);
+        let big = Endianness::Big.arrange(sample);
+        assert_eq!(little.to_ne_bytes(), [0x02, 0x01]);
+        assert_eq!(big.to_ne_bytes(), [0x01, 0x02]);
+    }
+
+    #[cfg(feature = "bytemuck")]
+    #[test]
+    fn host_bytes_match_native_order() {
+        let mut sector = [0; CD_FRAMEWORDS as usize];
+        sector[..2].copy_from_slice(&[0x0102, -2]);
+        let bytes = sector_host_bytes(&sector);
+        assert_eq!(
+            bytes[..4],
+            [0x0102i16.to_ne_bytes(), (-2i16).to_ne_bytes()].concat()
+        );
+        let samples = Samples::cd(sector.to_vec());
+        assert_eq!(samples.as_host_bytes(), &bytes[..]);
+    }
+
+    #[cfg(feature = "wav")]
+    #[test]
+    fn wav_round_trip() {
+        let path =
+            std::env::temp_dir().join(format!("cdparanoia-samples-{}.wav", std::process::id()));
+        let samples = Samples::new((-500..500).map(|i| i * 61).collect(), 2, 44100);
+        samples.write_wav(&path).unwrap();
+
+        let mut reader = hound::WavReader::open(&path).unwrap();
+        assert_eq!(reader.spec().channels, 2);
+        assert_eq!(reader.spec().sample_rate, 44100);
+        assert_eq!(reader.spec().bits_per_sample, 16);
+        let read: Vec<i16> = reader.samples().map(Result::unwrap).collect();
+        assert_eq!(read, *samples);
+        std::fs::remove_file(path).unwrap();
+    }
+
+    #[test]
+    fn trims_edge_silence() {
+        let mut samples = Samples::cd(vec![0, 1, -2, 0, 0, 90, 5, 0, 0, 0, 1, 1]);
+        assert_eq!(samples.trim_silence(2), 8);
+        assert_eq!(*samples, [0, 90, 5, 0]);
+        assert_eq!(samples.trim_silence(-1), 0);
+
+        let mut silent = Samples::cd(vec![0; 8]);
+        assert_eq!(silent.trim_silence(0), 8);
+        assert!(silent.is_empty());
+    }
+
+    #[test]
+    #[should_panic(expected = "whole frames")]
+    fn rejects_partial_frames() {
+        Samples::new(vec![0; 3], 2, 44100);
+    }
+
+    #[test]
+    #[should_panic(expected = "sample rate must be non-zero")]
+    fn rejects_zero_sample_rate() {
+        Samples::new(vec![0; 4], 2, 0);
+    }
+}
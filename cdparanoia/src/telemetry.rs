@@ -0,0 +1,378 @@
+//! Per-sector statistics derived from paranoia callback events.
+
+use std::{
+    collections::BTreeMap,
+    ffi::{c_int, c_long},
+    ops::RangeInclusive,
+};
+
+use crate::{
+    CdromParanoia, Concealment, Error, ParanoiaEvent, Samples, CD_FRAMESAMPLES, CD_FRAMEWORDS,
+};
+
+/// What the paranoia engine reported while producing the most recent
+/// sector.
+///
+/// The engine does not expose its internal state, so every field is
+/// derived from the events it passes to the read callback. It is reset at
+/// the start of every read.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct SectorTelemetry {
+    /// Number of `PARANOIA_CB_DRIFT` events: times the engine detected and
+    /// compensated for the drive's read position drifting.
+    pub drift: u32,
+
+    /// Overlap (in samples) the engine last switched to, from the position
+    /// argument of `PARANOIA_CB_OVERLAP`, if it adjusted it during the read.
+    pub overlap_adjust: Option<c_long>,
+
+    /// Number of `PARANOIA_CB_VERIFY` events: verification passes over
+    /// freshly read data.
+    pub verify_passes: u32,
+
+    /// Number of `PARANOIA_CB_SKIP` events: times the engine gave up on
+    /// verifying data and skipped ahead.
+    pub skips: u32,
+
+    /// Number of `PARANOIA_CB_BACKOFF` events: times the engine backed off
+    /// and re-read after dropped or duplicated data.
+    pub backoffs: u32,
+
+    /// Number of `PARANOIA_CB_READERR` events: reads the drive reported as
+    /// failed.
+    pub read_errors: u32,
+}
+
+impl SectorTelemetry {
+    /// Whether reading this sector needed retries: the drive reported a
+    /// read error, or the engine drifted, skipped or backed off.
+    ///
+    /// This is what counts as troubled throughout the crate, e.g. in
+    /// [`RipStats::troubled_sectors`].
+    pub fn is_troubled(&self) -> bool {
+        self.read_errors > 0 || self.drift > 0 || self.skips > 0 || self.backoffs > 0
+    }
+
+    pub(crate) fn record(&mut self, position: c_long, event: c_int) {
+        match event as u32 {
+            cdparanoia_sys::PARANOIA_CB_DRIFT => self.drift += 1,
+            cdparanoia_sys::PARANOIA_CB_OVERLAP => self.overlap_adjust = Some(position),
+            cdparanoia_sys::PARANOIA_CB_VERIFY => self.verify_passes += 1,
+            cdparanoia_sys::PARANOIA_CB_SKIP => self.skips += 1,
+            cdparanoia_sys::PARANOIA_CB_BACKOFF => self.backoffs += 1,
+            cdparanoia_sys::PARANOIA_CB_READERR => self.read_errors += 1,
+            _ => {}
+        }
+    }
+}
+
+/// Event counts over a whole rip, like the summary the `cdparanoia` tool
+/// prints, see [`TrackRip::stats`](crate::TrackRip::stats).
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct RipStats {
+    /// Sectors read.
+    pub sectors: u64,
+    /// Sectors whose read was troubled, see
+    /// [`SectorTelemetry::is_troubled`].
+    pub troubled_sectors: u64,
+    /// Number of events of each kind. Kinds that did not occur are absent.
+    pub events: BTreeMap<ParanoiaEvent, u64>,
+}
+
+impl RipStats {
+    /// Number of events of one kind.
+    pub fn count(&self, event: ParanoiaEvent) -> u64 {
+        self.events.get(&event).copied().unwrap_or(0)
+    }
+
+    /// Whether the drive failed a read or the engine gave up verifying
+    /// some data, so that the audio may not be exact.
+    pub fn had_unrecoverable(&self) -> bool {
+        self.count(ParanoiaEvent::Skip) > 0 || self.count(ParanoiaEvent::ReadError) > 0
+    }
+
+    /// Counts an event of the current sector.
+    pub(crate) fn record(&mut self, event: ParanoiaEvent) {
+        *self.events.entry(event).or_insert(0) += 1;
+    }
+
+    /// Counts a sector read, with the telemetry of its read.
+    pub(crate) fn end_sector(&mut self, telemetry: &SectorTelemetry) {
+        self.sectors += 1;
+        self.troubled_sectors += u64::from(telemetry.is_troubled());
+    }
+}
+
+/// Tracks whether any of the last few reads were troubled, see
+/// [`SectorTelemetry::is_troubled`].
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct RecoveryWindow {
+    window: u32,
+    reads_since_trouble: Option<u32>,
+}
+
+impl RecoveryWindow {
+    pub(crate) fn new(window: u32) -> Self {
+        Self {
+            window,
+            reads_since_trouble: None,
+        }
+    }
+
+    pub(crate) fn set_window(&mut self, window: u32) {
+        self.window = window;
+    }
+
+    pub(crate) fn record(&mut self, troubled: bool) {
+        self.reads_since_trouble = if troubled {
+            Some(0)
+        } else {
+            self.reads_since_trouble
+                .map(|reads| reads.saturating_add(1))
+        };
+    }
+
+    pub(crate) fn is_recovering(&self) -> bool {
+        self.reads_since_trouble
+            .is_some_and(|reads| reads < self.window)
+    }
+
+    pub(crate) fn reset(&mut self) {
+        self.reads_since_trouble = None;
+    }
+}
+
+/// Which sectors of a range the engine had to correct, from the positions
+/// of its `PARANOIA_CB_SKIP`, `PARANOIA_CB_REPAIR` and `PARANOIA_CB_FIXUP_*`
+/// events.
+///
+/// Returned by [`CdromParanoia::read_track_mapped`], e.g. to draw a damage
+/// map or decide whether to re-rip.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CorrectionMap {
+    first_sector: u64,
+    corrected: Vec<bool>,
+    /// Sectors with `PARANOIA_CB_SKIP` events, a subset of the corrected
+    /// ones.
+    skipped: Vec<bool>,
+}
+
+impl CorrectionMap {
+    /// Creates a map with no corrections over `range` (absolute, inclusive).
+    pub fn new(range: RangeInclusive<u64>) -> Self {
+        let (first_sector, last_sector) = range.into_inner();
+        let sectors = last_sector.saturating_add(1).saturating_sub(first_sector);
+        Self {
+            first_sector,
+            corrected: vec![false; sectors as usize],
+            skipped: vec![false; sectors as usize],
+        }
+    }
+
+    /// The sectors covered by the map.
+    pub fn sector_range(&self) -> RangeInclusive<u64> {
+        self.first_sector..=self.first_sector + self.corrected.len() as u64 - 1
+    }
+
+    /// Whether `sector` (absolute) was corrected. `false` outside the map.
+    pub fn is_corrected(&self, sector: u64) -> bool {
+        sector
+            .checked_sub(self.first_sector)
+            .and_then(|index| self.corrected.get(index as usize))
+            .copied()
+            .unwrap_or(false)
+    }
+
+    /// The corrected sectors (absolute), in ascending order.
+    pub fn corrected_sectors(&self) -> impl Iterator<Item = u64> + '_ {
+        self.corrected
+            .iter()
+            .zip(self.first_sector..)
+            .filter_map(|(&corrected, sector)| corrected.then_some(sector))
+    }
+
+    /// Whether the engine gave up verifying `sector` (absolute) and
+    /// skipped it, so that its audio is unreliable. `false` outside the
+    /// map.
+    pub fn is_skipped(&self, sector: u64) -> bool {
+        sector
+            .checked_sub(self.first_sector)
+            .and_then(|index| self.skipped.get(index as usize))
+            .copied()
+            .unwrap_or(false)
+    }
+
+    /// The skipped sectors (absolute), in ascending order.
+    pub fn skipped_sectors(&self) -> impl Iterator<Item = u64> + '_ {
+        self.skipped
+            .iter()
+            .zip(self.first_sector..)
+            .filter_map(|(&skipped, sector)| skipped.then_some(sector))
+    }
+
+    /// Fills in the skipped sectors of `samples`, the interleaved stereo
+    /// samples of the map's sectors, with `concealment`. Runs of adjacent
+    /// skipped sectors are concealed as one region.
+    pub fn conceal(&self, samples: &mut [i16], concealment: Concealment) {
+        let frames = CD_FRAMESAMPLES as usize;
+        let mut skipped = self
+            .skipped_sectors()
+            .map(|sector| (sector - self.first_sector) as usize)
+            .peekable();
+        while let Some(start) = skipped.next() {
+            let mut end = start + 1;
+            while skipped.next_if_eq(&end).is_some() {
+                end += 1;
+            }
+            concealment.apply(samples, 2, start * frames..end * frames);
+        }
+    }
+
+    /// Records a callback event, whose position is in samples from the
+    /// start of the disc. Events outside the map are ignored.
+    pub(crate) fn record(&mut self, position: c_long, event: c_int) {
+        let correction = matches!(
+            event as u32,
+            cdparanoia_sys::PARANOIA_CB_SKIP
+                | cdparanoia_sys::PARANOIA_CB_REPAIR
+                | cdparanoia_sys::PARANOIA_CB_FIXUP_EDGE
+                | cdparanoia_sys::PARANOIA_CB_FIXUP_ATOM
+                | cdparanoia_sys::PARANOIA_CB_FIXUP_DROPPED
+                | cdparanoia_sys::PARANOIA_CB_FIXUP_DUPED
+        );
+        let Ok(position) = u64::try_from(position) else {
+            return;
+        };
+        let sector = position / CD_FRAMEWORDS as u64;
+        if let Some(index) = sector.checked_sub(self.first_sector) {
+            if let Some(corrected) = self.corrected.get_mut(index as usize) {
+                *corrected |= correction;
+                self.skipped[index as usize] |= event as u32 == cdparanoia_sys::PARANOIA_CB_SKIP;
+            }
+        }
+    }
+}
+
+impl CdromParanoia {
+    /// Like [`read_track`](Self::read_track), also mapping which of the
+    /// track's sectors the engine corrected.
+    pub fn read_track_mapped(
+        &mut self,
+        track: u32,
+        callback: extern "C" fn(c_long, c_int),
+    ) -> Result<(Samples, CorrectionMap), Error> {
+        let range = self.drive.track_sector_range(track)?;
+        self.corrections = Some(CorrectionMap::new(range));
+        let result = self.read_track(track, callback);
+        let corrections = self.corrections.take().unwrap();
+        result.map(|samples| (samples, corrections))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn rip_stats_count_sectors_and_events() {
+        use cdparanoia_sys::{PARANOIA_CB_BACKOFF, PARANOIA_CB_READ, PARANOIA_CB_READERR};
+
+        fn read(stats: &mut RipStats, events: &[u32]) {
+            let mut telemetry = SectorTelemetry::default();
+            for &event in events {
+                telemetry.record(0, event as c_int);
+                stats.record(ParanoiaEvent::from_raw(event as c_int));
+            }
+            stats.end_sector(&telemetry);
+        }
+
+        let mut stats = RipStats::default();
+        read(&mut stats, &[PARANOIA_CB_READ, PARANOIA_CB_BACKOFF]);
+        read(&mut stats, &[PARANOIA_CB_READ]);
+        read(&mut stats, &[PARANOIA_CB_READERR]);
+
+        assert_eq!(stats.sectors, 3);
+        assert_eq!(stats.troubled_sectors, 2);
+        assert_eq!(stats.count(ParanoiaEvent::Read), 2);
+        assert_eq!(stats.count(ParanoiaEvent::FixupEdge), 0);
+        assert!(stats.had_unrecoverable());
+    }
+
+    #[test]
+    fn recovery_window_expires() {
+        let mut recovery = RecoveryWindow::new(2);
+        recovery.record(false);
+        assert!(!recovery.is_recovering());
+        recovery.record(true);
+        assert!(recovery.is_recovering());
+        recovery.record(false);
+        assert!(recovery.is_recovering());
+        recovery.record(false);
+        assert!(!recovery.is_recovering());
+
+        recovery.record(true);
+        recovery.reset();
+        assert!(!recovery.is_recovering());
+    }
+
+    #[test]
+    fn maps_corrections_by_position() {
+        let words = CD_FRAMEWORDS as c_long;
+        let mut map = CorrectionMap::new(100..=109);
+        map.record(
+            102 * words + 5,
+            cdparanoia_sys::PARANOIA_CB_FIXUP_EDGE as c_int,
+        );
+        map.record(107 * words, cdparanoia_sys::PARANOIA_CB_SKIP as c_int);
+        // Not a correction, and outside the map.
+        map.record(104 * words, cdparanoia_sys::PARANOIA_CB_VERIFY as c_int);
+        map.record(99 * words, cdparanoia_sys::PARANOIA_CB_REPAIR as c_int);
+        map.record(110 * words, cdparanoia_sys::PARANOIA_CB_REPAIR as c_int);
+        assert_eq!(map.corrected_sectors().collect::<Vec<_>>(), [102, 107]);
+        assert!(map.is_corrected(107));
+        assert!(!map.is_corrected(104));
+        assert!(!map.is_corrected(99));
+        assert_eq!(map.sector_range(), 100..=109);
+        assert_eq!(map.skipped_sectors().collect::<Vec<_>>(), [107]);
+        assert!(!map.is_skipped(102));
+    }
+
+    #[test]
+    fn conceals_skipped_sectors() {
+        let words = CD_FRAMEWORDS as c_long;
+        let mut map = CorrectionMap::new(100..=103);
+        map.record(101 * words, cdparanoia_sys::PARANOIA_CB_SKIP as c_int);
+        map.record(102 * words, cdparanoia_sys::PARANOIA_CB_SKIP as c_int);
+        map.record(103 * words, cdparanoia_sys::PARANOIA_CB_REPAIR as c_int);
+        let read: Vec<i16> = (1..=4)
+            .flat_map(|sector| [sector; CD_FRAMEWORDS as usize])
+            .collect();
+        let sector = |samples: &[i16], index: usize| {
+            samples[index * CD_FRAMEWORDS as usize..][..CD_FRAMEWORDS as usize].to_vec()
+        };
+
+        let mut silenced = read.clone();
+        map.conceal(&mut silenced, crate::RipConfig::default().concealment);
+        assert_eq!(sector(&silenced, 0), [1; CD_FRAMEWORDS as usize]);
+        assert_eq!(sector(&silenced, 1), [0; CD_FRAMEWORDS as usize]);
+        assert_eq!(sector(&silenced, 2), [0; CD_FRAMEWORDS as usize]);
+        assert_eq!(sector(&silenced, 3), [4; CD_FRAMEWORDS as usize]);
+
+        let mut held = read;
+        map.conceal(&mut held, Concealment::Hold);
+        assert_eq!(
+            held[..3 * CD_FRAMEWORDS as usize],
+            [1; 3 * CD_FRAMEWORDS as usize]
+        );
+        assert_eq!(sector(&held, 3), [4; CD_FRAMEWORDS as usize]);
+    }
+
+    #[test]
+    fn skips_and_backoffs_are_trouble() {
+        let mut telemetry = SectorTelemetry::default();
+        telemetry.record(0, cdparanoia_sys::PARANOIA_CB_VERIFY as c_int);
+        assert!(!telemetry.is_troubled());
+        telemetry.record(0, cdparanoia_sys::PARANOIA_CB_SKIP as c_int);
+        assert!(telemetry.is_troubled());
+    }
+}
@@ -0,0 +1,125 @@
+//! Test helpers.
+
+use std::{
+    ffi::{c_int, c_long, c_void},
+    mem::ManuallyDrop,
+};
+
+use crate::{CdromDrive, CdromParanoia, CD_FRAMEWORDS};
+
+/// An in-memory `cdrom_drive` with a synthetic TOC, for exercising the
+/// library's TOC functions without hardware.
+///
+/// The library never allocated this struct, so it must not reach
+/// `cdda_close`; the wrapped drive is never dropped.
+pub struct FakeDrive {
+    raw: Box<cdparanoia_sys::cdrom_drive>,
+    drive: ManuallyDrop<CdromDrive>,
+}
+
+impl FakeDrive {
+    /// Creates an opened drive with the given `(flags, start_sector)` TOC
+    /// entries and lead-out sector.
+    pub fn new(toc: &[(u8, i32)], lead_out: i32) -> Self {
+        let mut raw: Box<cdparanoia_sys::cdrom_drive> = Box::new(unsafe { std::mem::zeroed() });
+        raw.opened = 1;
+        raw.cdda_fd = -1;
+        raw.ioctl_fd = -1;
+        raw.set_speed = Some(set_fake_speed);
+        raw.tracks = toc.len() as _;
+        for (index, &(flags, start)) in toc.iter().enumerate() {
+            raw.disc_toc[index].bFlags = flags;
+            raw.disc_toc[index].bTrack = (index + 1) as u8;
+            raw.disc_toc[index].dwStartSector = start;
+        }
+        raw.disc_toc[toc.len()].bTrack = 0xaa;
+        raw.disc_toc[toc.len()].dwStartSector = lead_out;
+
+        let drive = unsafe { CdromDrive::from_raw(&mut *raw).unwrap() };
+        Self {
+            raw,
+            drive: ManuallyDrop::new(drive),
+        }
+    }
+
+    pub fn set_opened(&mut self, opened: bool) {
+        self.raw.opened = opened as _;
+    }
+
+    pub fn drive(&self) -> &CdromDrive {
+        &self.drive
+    }
+
+    pub fn drive_mut(&mut self) -> &mut CdromDrive {
+        &mut self.drive
+    }
+}
+
+/// `set_speed` of a [`FakeDrive`], which accepts any speed.
+unsafe extern "C" fn set_fake_speed(
+    _drive: *mut cdparanoia_sys::cdrom_drive,
+    _speed: c_int,
+) -> c_int {
+    0
+}
+
+/// The sample a [`FakeParanoia`] drive holds at `index`, counted from the
+/// first sample of sector 0.
+pub fn fake_sample(index: u64) -> i16 {
+    index as i16
+}
+
+/// `read_audio` of a [`FakeParanoia`] drive, which reads the same
+/// [`fake_sample`]s every time.
+unsafe extern "C" fn read_fake_audio(
+    _drive: *mut cdparanoia_sys::cdrom_drive,
+    buffer: *mut c_void,
+    begin: c_long,
+    sectors: c_long,
+) -> c_long {
+    let words = sectors as usize * CD_FRAMEWORDS as usize;
+    let buffer = unsafe { std::slice::from_raw_parts_mut(buffer.cast::<i16>(), words) };
+    let first = begin as u64 * u64::from(CD_FRAMEWORDS);
+    for (sample, index) in buffer.iter_mut().zip(first..) {
+        *sample = fake_sample(index);
+    }
+    sectors
+}
+
+/// A paranoia session over a [`FakeDrive`] whose sectors read back as
+/// [`fake_sample`]s, a drive that never errs.
+///
+/// Like the drive, the session must not be dropped normally; only its
+/// paranoia state is freed.
+pub struct FakeParanoia {
+    _fake: FakeDrive,
+    paranoia: ManuallyDrop<CdromParanoia>,
+}
+
+impl FakeParanoia {
+    pub fn new(toc: &[(u8, i32)], lead_out: i32) -> Self {
+        let mut fake = FakeDrive::new(toc, lead_out);
+        fake.raw.read_audio = Some(read_fake_audio);
+        fake.raw.nsectors = 8;
+        fake.raw.bigendianp = cfg!(target_endian = "big").into();
+        let drive = unsafe { CdromDrive::from_raw(&mut *fake.raw).unwrap() };
+        let paranoia = CdromParanoia::init(drive).unwrap();
+        Self {
+            _fake: fake,
+            paranoia: ManuallyDrop::new(paranoia),
+        }
+    }
+
+    pub fn paranoia(&mut self) -> &mut CdromParanoia {
+        &mut self.paranoia
+    }
+}
+
+impl Drop for FakeParanoia {
+    fn drop(&mut self) {
+        let paranoia = unsafe { ManuallyDrop::take(&mut self.paranoia) };
+        let (drive, raw) = paranoia.into_raw();
+        unsafe { cdparanoia_sys::paranoia_free(raw) };
+        std::mem::forget(drive);
+    }
+}
@@ -0,0 +1,108 @@
+//! Decoding of text stored on the disc (CD-TEXT, MCN, ISRC).
+
+use std::borrow::Cow;
+
+/// Character code of a CD-TEXT block, as given by its block size
+/// information.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CdTextEncoding {
+    /// 0x00: ISO 8859-1 (Latin-1).
+    Latin1,
+
+    /// 0x01: ISO 646 (ASCII).
+    Ascii,
+
+    /// 0x80: MS-JIS (Shift JIS).
+    MsJis,
+
+    /// 0x81: Korean (KS C 5601).
+    Korean,
+
+    /// 0x82: Mandarin (GB 2312).
+    Mandarin,
+}
+
+impl CdTextEncoding {
+    pub fn from_raw(raw: u8) -> Option<Self> {
+        match raw {
+            0x00 => Some(Self::Latin1),
+            0x01 => Some(Self::Ascii),
+            0x80 => Some(Self::MsJis),
+            0x81 => Some(Self::Korean),
+            0x82 => Some(Self::Mandarin),
+            _ => None,
+        }
+    }
+
+    pub fn as_raw(&self) -> u8 {
+        match self {
+            Self::Latin1 => 0x00,
+            Self::Ascii => 0x01,
+            Self::MsJis => 0x80,
+            Self::Korean => 0x81,
+            Self::Mandarin => 0x82,
+        }
+    }
+}
+
+/// Decodes a string read from the disc, stopping at the first NUL.
+///
+/// ASCII and Latin-1 are decoded exactly, borrowing when the text is plain
+/// ASCII. The double-byte encodings are not supported by this crate: their
+/// ASCII bytes are kept and every other byte becomes U+FFFD.
+pub fn decode_cd_string(bytes: &[u8], encoding: CdTextEncoding) -> Cow<'_, str> {
+    let bytes = match bytes.iter().position(|&b| b == 0) {
+        Some(end) => &bytes[..end],
+        None => bytes,
+    };
+    if bytes.is_ascii() {
+        // Checked above, and ASCII is valid UTF-8.
+        return Cow::Borrowed(std::str::from_utf8(bytes).unwrap());
+    }
+    let decoded = match encoding {
+        // Every Latin-1 byte is the Unicode code point of the same value.
+        CdTextEncoding::Latin1 => bytes.iter().map(|&b| b as char).collect(),
+        CdTextEncoding::Ascii
+        | CdTextEncoding::MsJis
+        | CdTextEncoding::Korean
+        | CdTextEncoding::Mandarin => bytes
+            .iter()
+            .map(|&b| {
+                if b.is_ascii() {
+                    b as char
+                } else {
+                    char::REPLACEMENT_CHARACTER
+                }
+            })
+            .collect(),
+    };
+    Cow::Owned(decoded)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn latin1_performer() {
+        let name = b"Bj\xf6rk Gu\xf0mundsd\xf3ttir\0\0";
+        assert_eq!(
+            decode_cd_string(name, CdTextEncoding::Latin1),
+            "Björk Guðmundsdóttir"
+        );
+    }
+
+    #[test]
+    fn ascii_is_borrowed() {
+        let decoded = decode_cd_string(b"USRC17607839\0", CdTextEncoding::Ascii);
+        assert!(matches!(decoded, Cow::Borrowed("USRC17607839")));
+    }
+
+    #[test]
+    fn unsupported_bytes_are_replaced() {
+        assert_eq!(
+            decode_cd_string(b"A\x82\xa0B", CdTextEncoding::MsJis),
+            "A\u{fffd}\u{fffd}B"
+        );
+    }
+}